@@ -0,0 +1,44 @@
+use std::io::{self, BufRead, Write};
+use uno::engine::Position;
+use uno::moves::Move;
+
+// Reference engine for the text protocol in uno::engine. Plays the card whose color it holds the most of,
+// keeps wilds for when nothing else fits and only draws when it has to.
+fn choose(position: &Position) -> Option<&Move> {
+    let held = |color: &str| position.hand.iter().filter(|card| card.starts_with(color)).count();
+    position.moves.iter().max_by_key(|play| match play {
+        Move::Play { card, color } if card.starts_with("WILD") => 1 + held(color),
+        Move::Play { color, .. } => 100 + held(color),
+        Move::PlayMany { card, colors } if card.starts_with("WILD") => colors.len() + held(&colors[0]),
+        Move::PlayMany { colors, .. } => 100 + colors.len() + held(&colors[colors.len() - 1]),
+        Move::Draw => 0,
+    })
+}
+
+fn main() {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut position = Position::default();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break
+        };
+        let reply = match line.split_whitespace().next() {
+            Some("uno") => Some("id name uno-engine\nunook".to_string()),
+            Some("go") => Some(match choose(&position) {
+                Some(play) => format!("bestmove {}", play),
+                None => "bestmove draw".to_string(),
+            }),
+            Some("quit") => break,
+            _ => {
+                position.read(line.as_str());
+                None
+            }
+        };
+        if let Some(reply) = reply {
+            if writeln!(stdout, "{}", reply).and_then(|_| stdout.flush()).is_err() {
+                break
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::exit;
+use uno::history::GameLog;
+use uno::notation;
+use uno::replay::{View, Viewer};
+
+fn load(path: &str) -> Result<GameLog, String> {
+    let input = fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+    if input.trim_start().starts_with('{') {
+        GameLog::from_json(input.as_str())
+    }
+    else {
+        notation::parse(input.as_str()).map_err(|err| format!("{}: {}", path, err))
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let Some(path) = args.get(1) else {
+        eprintln!("Usage: uno-replay <game.json|game.uno> [--turn N] [--seat P1]");
+        exit(2)
+    };
+    let log = match load(path) {
+        Ok(log) => log,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1)
+        }
+    };
+    let mut viewer = Viewer::new(log);
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|pos| args.get(pos + 1)).cloned();
+    if let Some(seat) = option("--seat") {
+        match viewer.seat(seat.as_str()) {
+            Some(seat) => viewer.set_view(View::Seat(seat)),
+            None => {
+                eprintln!("Unknown seat '{}'", seat);
+                exit(1)
+            }
+        }
+    }
+    if let Some(turn) = option("--turn") {
+        match viewer.command(format!("g {}", turn).as_str()) {
+            Ok(out) => {
+                println!("{}", out.unwrap_or_default());
+                exit(0)
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                exit(1)
+            }
+        }
+    }
+    match viewer.render() {
+        Ok(out) => println!("{}", out),
+        Err(err) => eprintln!("{}", err),
+    }
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break
+        }
+        match viewer.command(line.as_str()) {
+            Ok(Some(out)) => println!("{}", out),
+            Ok(None) => break,
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::ismcts::Ismcts;
+use crate::moves::Move;
+use crate::player::PlayerId;
+use crate::rules::RuleKey;
+use crate::strategy::Strategy;
+use crate::uno_game::UnoGame;
+
+const HARD_ITERATIONS: usize = 1000;
+const HARD_TIME_LIMIT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Personality {
+    /// How eagerly draw cards, skips and reverses are thrown at the next player.
+    pub aggression: f64,
+    /// How strongly wilds and the best held color are saved for later.
+    pub hoarding: f64,
+    /// Chance of noticing a missed UNO call.
+    pub callouts: f64,
+    /// Chance of forgetting to say UNO.
+    pub forgets_uno: f64,
+}
+
+pub struct BotPreset {
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub difficulty: Difficulty,
+    pub personality: Personality,
+}
+
+pub const BOT_PRESETS: &[BotPreset] = &[
+    BotPreset {
+        name: "easy",
+        desc: "Plays any card it can and sometimes forgets to say UNO.",
+        difficulty: Difficulty::Easy,
+        personality: Personality { aggression: 0.5, hoarding: 0.0, callouts: 0.2, forgets_uno: 0.35 },
+    },
+    BotPreset {
+        name: "medium",
+        desc: "Picks sensible cards and keeps an ear out for UNO calls.",
+        difficulty: Difficulty::Medium,
+        personality: Personality { aggression: 0.5, hoarding: 0.3, callouts: 0.6, forgets_uno: 0.05 },
+    },
+    BotPreset {
+        name: "hard",
+        desc: "Thinks ahead about the cards it can't see.",
+        difficulty: Difficulty::Hard,
+        personality: Personality { aggression: 0.5, hoarding: 0.5, callouts: 0.95, forgets_uno: 0.0 },
+    },
+    BotPreset {
+        name: "bully",
+        desc: "Dumps every draw card it has on the next player.",
+        difficulty: Difficulty::Medium,
+        personality: Personality { aggression: 1.0, hoarding: 0.1, callouts: 0.8, forgets_uno: 0.05 },
+    },
+    BotPreset {
+        name: "hoarder",
+        desc: "Sits on its wilds and its best color until the very end.",
+        difficulty: Difficulty::Medium,
+        personality: Personality { aggression: 0.2, hoarding: 1.0, callouts: 0.4, forgets_uno: 0.1 },
+    },
+];
+
+impl BotPreset {
+    pub fn find(name: &str) -> Option<&'static BotPreset> {
+        BOT_PRESETS.iter().find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+pub struct RandomMoves {
+    rng: StdRng,
+}
+
+impl RandomMoves {
+    pub fn new(seed: u64) -> Self {
+        RandomMoves { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Strategy for RandomMoves {
+    fn name(&self) -> String {
+        "random".to_string()
+    }
+
+    fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move> {
+        game.legal_moves(player).choose(&mut self.rng).cloned()
+    }
+}
+
+pub struct Heuristic {
+    personality: Personality,
+    rng: StdRng,
+}
+
+impl Heuristic {
+    pub fn new(personality: Personality, seed: u64) -> Self {
+        Heuristic { personality, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    fn score(&mut self, game: &UnoGame, player: &PlayerId, play: &Move) -> f64 {
+        let (card, color, extra) = match play {
+            Move::Play { card, color } => (card, color, 0),
+            Move::PlayMany { card, colors } => (card, &colors[colors.len() - 1], colors.len() - 1),
+            Move::Draw => return -1.0,
+        };
+        let hand = game.get_player(player).map(|player| player.cards()).unwrap_or(&[]);
+        let share = hand.iter().filter(|held| held.color() == color).count() as f64 / hand.len().max(1) as f64;
+        let next_cards = game.players().nth(1).map(|next| next.cards().len()).unwrap_or(0);
+        let threat = if next_cards <= 2 { 2.0 } else { 0.0 };
+        let traits = self.personality;
+        let mut score = 1.0 + extra as f64 * 0.5 + self.rng.gen::<f64>() * 0.1;
+        match card.as_str() {
+            "+2" | "WILD+4" => score += traits.aggression * 2.0 + threat,
+            "SKIP" | "REVERSE" => score += traits.aggression + threat / 2.0,
+            number => score += number.parse::<f64>().map(|number| number / 20.0).unwrap_or(0.0),
+        }
+        if card.starts_with("WILD") {
+            score += share * 2.0 - traits.hoarding * 2.0;
+        }
+        else {
+            score += (1.0 - 2.0 * traits.hoarding) * share;
+        }
+        score
+    }
+}
+
+impl Strategy for Heuristic {
+    fn name(&self) -> String {
+        "heuristic".to_string()
+    }
+
+    fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move> {
+        let mut best: Option<(f64, Move)> = None;
+        for play in game.legal_moves(player) {
+            let score = self.score(game, player, &play);
+            if best.as_ref().is_none_or(|(top, _)| score > *top) {
+                best = Some((score, play));
+            }
+        }
+        best.map(|(_, play)| play)
+    }
+}
+
+pub struct Bot {
+    preset: &'static BotPreset,
+    strategy: Box<dyn Strategy>,
+    rng: StdRng,
+}
+
+impl Bot {
+    pub fn new(preset: &'static BotPreset, seed: u64) -> Self {
+        let strategy: Box<dyn Strategy> = match preset.difficulty {
+            Difficulty::Easy => Box::new(RandomMoves::new(seed)),
+            Difficulty::Medium => Box::new(Heuristic::new(preset.personality, seed)),
+            Difficulty::Hard => Box::new(Ismcts::new(HARD_ITERATIONS).with_time_limit(HARD_TIME_LIMIT).with_seed(seed)),
+        };
+        Bot {
+            preset,
+            strategy,
+            rng: StdRng::seed_from_u64(seed.wrapping_add(1)),
+        }
+    }
+
+    pub fn preset(&self) -> &'static BotPreset {
+        self.preset
+    }
+
+    pub fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move> {
+        self.strategy.choose(game, player)
+    }
+
+    pub fn says_uno(&mut self) -> bool {
+        !self.rng.gen_bool(self.preset.personality.forgets_uno)
+    }
+
+    pub fn wants_callout(&mut self, game: &UnoGame, player: &PlayerId) -> bool {
+        let rules = game.rules();
+        let penalty = rules.int(RuleKey::CalloutPenalty);
+        if !rules.bool(RuleKey::Callouts) || penalty == 0 {
+            return false
+        }
+        let suspects = game.players()
+            .filter(|other| other.id() != player && other.cards().len() == 1)
+            .collect::<Vec<_>>();
+        if suspects.is_empty() {
+            return false
+        }
+        if self.rng.gen_bool(self.preset.personality.callouts) {
+            return suspects.iter().any(|other| !other.has_called_uno())
+        }
+        // Didn't catch whether they said it, so only gamble when a catch is worth more than a miss costs.
+        penalty > rules.int(RuleKey::FalseCalloutPenalty) && self.rng.gen_bool(0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bots_play_until_someone_wins() {
+        let mut game = UnoGame::with_seed(8);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        assert!(game.add_bot(&host, "grandmaster").is_err());
+        for preset in ["easy", "medium", "Bully", "hoarder"] {
+            game.add_bot(&host, preset).unwrap();
+        }
+        assert_eq!(game.players().filter(|player| game.is_bot(player.id())).count(), 4);
+        game.start(&host).unwrap();
+        for _ in 0..500 {
+            if game.is_over() {
+                break
+            }
+            if game.get_curr_player().id() == &host {
+                let play = game.legal_moves(&host)[0].clone();
+                game.make_move(&play).unwrap();
+            }
+            game.run_bots();
+        }
+        assert!(game.is_over());
+    }
+}
@@ -1,5 +1,32 @@
 use std::cmp::Ordering;
 use std::fmt::Display;
+use crate::i18n::{Locale, Msg};
+
+pub const COLORS: [&str; 4] = ["R", "G", "B", "Y"];
+
+/// The name of a color code such as "R" in the given locale. Anything that isn't a color is returned as is.
+pub fn color_text(color: &str, locale: Locale) -> String {
+    let msg = match color {
+        "R" => Msg::ColorRed,
+        "G" => Msg::ColorGreen,
+        "B" => Msg::ColorBlue,
+        "Y" => Msg::ColorYellow,
+        _ => return color.to_string(),
+    };
+    msg.text(locale)
+}
+
+pub fn face_text(id: &str, locale: Locale) -> String {
+    let msg = match id {
+        "SKIP" => Msg::FaceSkip,
+        "REVERSE" => Msg::FaceReverse,
+        "WILD" => Msg::FaceWild,
+        "WILD+4" => Msg::FaceWildFour,
+        _ => return id.to_string(),
+    };
+    msg.text(locale)
+}
+
 pub struct Card {
     pub(crate) num: i32,
     pub(crate) id: String,
@@ -10,7 +37,7 @@ pub struct Card {
 impl Card {
     pub(crate) fn new(id: String, color : &str, num: i32) -> Self {
         let wild: bool = {
-            color == "wild" || color == ""
+            color == "wild" || color.is_empty()
         };
         let colour = String::from(color);
         Self {
@@ -20,7 +47,19 @@ impl Card {
             wild
         }
     }
-    fn get_color_name(&self) -> &str {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn color(&self) -> &str {
+        &self.color
+    }
+    pub fn is_wild(&self) -> bool {
+        self.wild
+    }
+    pub fn can_play_on(&self, top: &Card) -> bool {
+        self.wild || top.color.is_empty() || top.id == self.id || top.color == self.color
+    }
+    pub fn get_color_name(&self) -> &str {
         match self.color.as_str() {
             "R" => "Red",
             "G" => "Green",
@@ -29,7 +68,17 @@ impl Card {
             _ => ""
         }
     }
-    fn get_color_code(&self) -> i32 {
+    /// The card as shown to players. Wilds show their color once one has been chosen.
+    pub fn text(&self, locale: Locale) -> String {
+        let face = face_text(&self.id, locale);
+        if COLORS.contains(&self.color.as_str()) {
+            Msg::CardName { color: color_text(&self.color, locale), face }.text(locale)
+        }
+        else {
+            face
+        }
+    }
+    pub fn get_color_code(&self) -> i32 {
         match self.color.as_str() {
             "R" => 0xff5555,
             "G" => 0x55aa55,
@@ -38,6 +87,13 @@ impl Card {
             _ => 0x080808
         }
     }
+    pub fn points(&self) -> i32 {
+        match self.id.as_str() {
+            "SKIP" | "REVERSE" | "+2" => 20,
+            "WILD" | "WILD+4" => 50,
+            number => number.parse().unwrap_or(0),
+        }
+    }
 
     fn get_value(&self) -> i32 {
         let mut val: i32 = 0;
@@ -65,13 +121,7 @@ impl Card {
 
 impl Display for Card {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.wild {
-            write!(f, "{}", self.id)
-        }
-        else {
-            let color_name = self.get_color_name();
-            write!(f, "{} {}", color_name, self.id)
-        }
+        write!(f, "{}", self.text(Locale::En))
     }
 }
 
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use crate::card::{color_text, face_text};
+use crate::i18n::{Locale, Msg};
+
+const COLORS: &[(&str, &str)] = &[
+    ("yellow", "Y"),
+    ("green", "G"),
+    ("blue", "B"),
+    ("red", "R"),
+    ("y", "Y"),
+    ("g", "G"),
+    ("b", "B"),
+    ("r", "R"),
+];
+
+const VALUES: &[(&str, &str)] = &[
+    ("zero", "0"),
+    ("one", "1"),
+    ("two", "2"),
+    ("three", "3"),
+    ("four", "4"),
+    ("five", "5"),
+    ("six", "6"),
+    ("seven", "7"),
+    ("eight", "8"),
+    ("nine", "9"),
+    ("skip", "SKIP"),
+    ("reverse", "REVERSE"),
+    ("+2", "+2"),
+    ("draw2", "+2"),
+    ("d2", "+2"),
+    ("wild", "WILD"),
+    ("wild+4", "WILD+4"),
+    ("wild4", "WILD+4"),
+    ("w4", "WILD+4"),
+    ("+4", "WILD+4"),
+    ("draw4", "WILD+4"),
+    ("d4", "WILD+4"),
+];
+
+pub const DEFAULT_ALIASES: &[(&str, &str)] = &[
+    ("w", "WILD"),
+    ("w+4", "WILD+4"),
+    ("rev", "REVERSE"),
+    ("nou", "REVERSE"),
+    ("s", "SKIP"),
+];
+
+const MAX_ALIAS_LENGTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aliases {
+    table: BTreeMap<String, String>,
+}
+
+impl Default for Aliases {
+    fn default() -> Self {
+        Aliases {
+            table: DEFAULT_ALIASES.iter().map(|(alias, card)| (alias.to_string(), card.to_string())).collect(),
+        }
+    }
+}
+
+impl Aliases {
+    pub fn empty() -> Self {
+        Aliases { table: BTreeMap::new() }
+    }
+
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.table.get(alias.to_lowercase().as_str()).map(|card| card.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.table.iter().map(|(alias, card)| (alias.as_str(), card.as_str()))
+    }
+
+    pub fn add(&mut self, alias: &str, card: &str) -> Result<(String, String), Msg> {
+        let alias = alias.trim().to_lowercase();
+        if alias.is_empty() || alias.chars().count() > MAX_ALIAS_LENGTH || words(alias.as_str()) != [alias.clone()] {
+            return Err(Msg::AliasInvalid { alias, max: MAX_ALIAS_LENGTH })
+        }
+        let card = builtin(card.trim().to_lowercase().as_str()).ok_or_else(|| Msg::AliasUnknownCard { card: card.to_string() })?;
+        match token(alias.as_str(), &Aliases::empty()) {
+            Some(Token::Color(color)) => return Err(Msg::AliasIsColor { alias, color: color_name(color).to_string() }),
+            Some(Token::Value(value)) => return Err(Msg::AliasIsCard { alias, card: value }),
+            Some(Token::Card(color, value)) => return Err(Msg::AliasIsCard { alias, card: CardSpec { id: value, color: Some(color.to_string()) }.to_string() }),
+            None => {}
+        }
+        if let Some(existing) = self.table.get(&alias) {
+            return Err(Msg::AliasTaken { alias, card: existing.clone() })
+        }
+        self.table.insert(alias.clone(), card.clone());
+        Ok((alias, card))
+    }
+
+    pub fn remove(&mut self, alias: &str) -> Option<String> {
+        self.table.remove(alias.trim().to_lowercase().as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CardSpec {
+    pub id: String,
+    pub color: Option<String>,
+}
+
+impl CardSpec {
+    pub fn is_wild(&self) -> bool {
+        self.id == "WILD" || self.id == "WILD+4"
+    }
+
+    pub(crate) fn text(&self, locale: Locale) -> String {
+        match &self.color {
+            Some(color) => Msg::CardName { color: color_text(color, locale), face: face_text(&self.id, locale) }.text(locale),
+            None => face_text(&self.id, locale),
+        }
+    }
+}
+
+impl Display for CardSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.is_wild(), &self.color) {
+            (true, Some(color)) => write!(f, "{} {}", self.id, color_name(color)),
+            (false, Some(color)) => write!(f, "{} {}", color_name(color), self.id),
+            (_, None) => write!(f, "{}", self.id),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    UnknownWord { word: String, hint: Option<String> },
+    MissingCard,
+    TwoColors(String, String),
+    TwoCards(String, String),
+}
+
+impl ParseError {
+    pub fn msg(&self) -> Msg {
+        match self {
+            ParseError::Empty => Msg::EmptyCard,
+            ParseError::UnknownWord { word, hint: None } => Msg::UnknownWord { word: word.clone() },
+            ParseError::UnknownWord { word, hint: Some(hint) } => Msg::UnknownWordHint { word: word.clone(), hint: hint.clone() },
+            ParseError::MissingCard => Msg::MissingCard,
+            ParseError::TwoColors(first, second) => Msg::TwoColors { first: color_name(first).to_string(), second: color_name(second).to_string() },
+            ParseError::TwoCards(first, second) => Msg::TwoCards { first: first.clone(), second: second.clone() },
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.msg().text(Locale::En))
+    }
+}
+
+enum Token {
+    Color(&'static str),
+    Value(String),
+    Card(&'static str, String),
+}
+
+pub(crate) fn color_name(color: &str) -> &str {
+    match color {
+        "R" => "Red",
+        "G" => "Green",
+        "B" => "Blue",
+        "Y" => "Yellow",
+        _ => color,
+    }
+}
+
+fn color_of(word: &str) -> Option<&'static str> {
+    COLORS.iter().find(|(name, _)| *name == word).map(|(_, color)| *color)
+}
+
+fn builtin(word: &str) -> Option<String> {
+    if word.len() == 1 && word.chars().all(|c| c.is_ascii_digit()) {
+        return Some(word.to_string())
+    }
+    VALUES.iter().find(|(name, _)| *name == word).map(|(_, id)| id.to_string())
+}
+
+fn token(word: &str, aliases: &Aliases) -> Option<Token> {
+    let value_of = |word: &str| builtin(word).or_else(|| aliases.get(word).map(|card| card.to_string()));
+    if let Some(color) = color_of(word) {
+        return Some(Token::Color(color))
+    }
+    if let Some(value) = value_of(word) {
+        return Some(Token::Value(value))
+    }
+    for (name, color) in COLORS {
+        let rest = word.strip_prefix(name).or_else(|| word.strip_suffix(name));
+        if let Some(value) = rest.and_then(value_of) {
+            return Some(Token::Card(color, value))
+        }
+    }
+    None
+}
+
+fn words(input: &str) -> Vec<String> {
+    let input = input.to_lowercase();
+    let raw = input
+        .split(|c: char| c.is_whitespace() || ",;/-_".contains(c))
+        .map(|word| word.trim_matches(|c: char| ".!?:()[]'\"*".contains(c)))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    let mut words = Vec::new();
+    let mut idx = 0;
+    while idx < raw.len() {
+        let joined = match (raw[idx], raw.get(idx + 1)) {
+            ("draw", Some(&("2" | "two"))) => Some("+2"),
+            ("draw", Some(&("4" | "four"))) => Some("+4"),
+            _ => None,
+        };
+        match joined {
+            Some(word) => {
+                words.push(word.to_string());
+                idx += 2;
+            }
+            None => {
+                words.push(raw[idx].to_string());
+                idx += 1;
+            }
+        }
+    }
+    words
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn hint(word: &str) -> Option<String> {
+    if word.chars().count() > 12 {
+        return None
+    }
+    let allowed = if word.chars().count() > 3 { 2 } else { 1 };
+    COLORS.iter().map(|(name, _)| *name)
+        .chain(VALUES.iter().map(|(name, _)| *name))
+        .filter(|name| name.len() > 1)
+        .map(|name| (distance(word, name), name))
+        .filter(|(distance, _)| *distance <= allowed)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.to_string())
+}
+
+pub fn parse(input: &str, aliases: &Aliases) -> Result<CardSpec, ParseError> {
+    let words = words(input);
+    if words.is_empty() {
+        return Err(ParseError::Empty)
+    }
+    let mut colors: Vec<&str> = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+    for word in &words {
+        match token(word, aliases) {
+            Some(Token::Color(color)) => colors.push(color),
+            Some(Token::Value(value)) => values.push(value),
+            Some(Token::Card(color, value)) => {
+                colors.push(color);
+                values.push(value);
+            }
+            None => return Err(ParseError::UnknownWord { word: word.clone(), hint: hint(word) }),
+        }
+    }
+    if values.iter().any(|value| value == "WILD") && values.iter().any(|value| value == "4" || value == "WILD+4") {
+        values.retain(|value| value != "WILD" && value != "4");
+        values.push("WILD+4".to_string());
+    }
+    let mut unique: Vec<&str> = Vec::new();
+    for color in colors {
+        if !unique.contains(&color) {
+            unique.push(color);
+        }
+    }
+    let colors = unique;
+    values.sort();
+    values.dedup();
+    if colors.len() > 1 {
+        return Err(ParseError::TwoColors(colors[0].to_string(), colors[1].to_string()))
+    }
+    match values.as_slice() {
+        [] => Err(ParseError::MissingCard),
+        [id] => Ok(CardSpec { id: id.clone(), color: colors.first().map(|color| color.to_string()) }),
+        [first, second, ..] => Err(ParseError::TwoCards(first.clone(), second.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::player::PlayerId;
+    use crate::uno_game::UnoGame;
+    use super::*;
+
+    fn parse_default(input: &str) -> Result<CardSpec, ParseError> {
+        parse(input, &Aliases::default())
+    }
+
+    fn spec(id: &str, color: Option<&str>) -> Result<CardSpec, ParseError> {
+        Ok(CardSpec { id: id.to_string(), color: color.map(|color| color.to_string()) })
+    }
+
+    #[test]
+    fn reads_loose_input() {
+        assert_eq!(parse_default("r5"), spec("5", Some("R")));
+        assert_eq!(parse_default("5R"), spec("5", Some("R")));
+        assert_eq!(parse_default("red five"), spec("5", Some("R")));
+        assert_eq!(parse_default("+2 blue"), spec("+2", Some("B")));
+        assert_eq!(parse_default("wild4 green"), spec("WILD+4", Some("G")));
+        assert_eq!(parse_default("Wild Draw Four, yellow!"), spec("WILD+4", Some("Y")));
+        assert_eq!(parse_default("G REVERSE"), spec("REVERSE", Some("G")));
+        assert_eq!(parse_default("R rev"), spec("REVERSE", Some("R")));
+        assert_eq!(parse_default("skip"), spec("SKIP", None));
+        assert_eq!(parse_default("r"), Err(ParseError::MissingCard));
+        assert_eq!(parse_default("red fiev"), Err(ParseError::UnknownWord { word: "fiev".to_string(), hint: Some("five".to_string()) }));
+        assert_eq!(parse_default("red blue 5"), Err(ParseError::TwoColors("R".to_string(), "B".to_string())));
+        assert_eq!(parse_default("  "), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn alias_conflicts_are_rejected() {
+        let mut aliases = Aliases::default();
+        assert_eq!(aliases.add("R", "reverse").unwrap_err().key(), "alias_is_color");
+        assert_eq!(aliases.add("skip", "+2").unwrap_err().key(), "alias_is_card");
+        assert_eq!(aliases.add("b5", "skip").unwrap_err().key(), "alias_is_card");
+        assert_eq!(aliases.add("S", "+2").unwrap_err().key(), "alias_taken");
+        assert_eq!(aliases.add("nope", "joker").unwrap_err().key(), "alias_unknown_card");
+        aliases.add("Pass", "skip").unwrap();
+        aliases.remove("nou").unwrap();
+        assert_eq!(parse("pass green", &aliases), spec("SKIP", Some("G")));
+        assert!(parse("nou green", &aliases).is_err());
+    }
+
+    #[test]
+    fn random_input_never_panics() {
+        let pieces = ["r", "G", "blue", "5", "+", "2", "4", "wild", "draw", "skip", "rev", " ", ",", "-", "é", "🂡", "\u{0}", "ß", "four", "w+4", "İ"];
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut game = UnoGame::with_seed(42);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.start(&host).unwrap();
+        for _ in 0..5000 {
+            let len = rng.gen_range(0..8);
+            let input = (0..len).map(|_| pieces[rng.gen_range(0..pieces.len())]).collect::<Vec<&str>>().join("");
+            let _ = parse_default(input.as_str()).map(|spec| spec.to_string());
+            if game.is_over() {
+                break
+            }
+            let _ = game.play(input);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where a game gets the time from, in milliseconds since the Unix epoch.
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Clone, Default)]
+pub struct ManualClock(Rc<Cell<u64>>);
+
+impl ManualClock {
+    pub fn new(start: u64) -> Self {
+        ManualClock(Rc::new(Cell::new(start)))
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by.as_millis() as u64);
+    }
+
+    pub fn set(&self, now: u64) {
+        self.0.set(now);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
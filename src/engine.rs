@@ -0,0 +1,378 @@
+//! Text protocol for bots that run as separate processes, one command per line over stdin/stdout.
+//!
+//! ```text
+//! > uno 1
+//! < id name <engine name>      (optional)
+//! < unook
+//! > position
+//! > seat <player id>
+//! > top <color> <card>
+//! > hand <color> <card>         (one line per card, wilds have no color)
+//! > player <player id> <cards>  (one line per seat in turn order, the seat to move first)
+//! > deck <cards>
+//! > move play <color> <card>    (one line per legal move)
+//! > move play <color> <card> & <color> <card> ...  (Multi-Play, same card each time)
+//! > move draw
+//! > go <milliseconds>
+//! < bestmove play <color> <card>
+//! > quit
+//! ```
+//!
+//! Player ids are sent as one word, with whitespace and '%' percent-encoded (`a b` becomes `a%20b`).
+//! Engines should ignore lines they don't understand.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::history::Action;
+use crate::moves::Move;
+use crate::player::PlayerId;
+use crate::strategy::Strategy;
+use crate::uno_game::UnoGame;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_RESTARTS: usize = 3;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub seat: String,
+    pub top: String,
+    pub hand: Vec<String>,
+    pub players: Vec<(String, usize)>,
+    pub deck: usize,
+    pub moves: Vec<Move>,
+}
+
+impl Position {
+    pub fn from_game(game: &UnoGame, seat: &PlayerId) -> Position {
+        let top = game.get_curr_card();
+        Position {
+            seat: seat.to_string(),
+            top: Action::play_text(top.id(), top.color()),
+            hand: game.get_player(seat)
+                .map(|player| player.cards().iter().map(|card| Action::play_text(card.id(), card.color())).collect())
+                .unwrap_or_default(),
+            players: game.players().map(|player| (player.id().to_string(), player.cards().len())).collect(),
+            deck: game.deck_size(),
+            moves: game.legal_moves(seat),
+        }
+    }
+
+    pub fn to_lines(&self) -> Vec<String> {
+        let mut lines = vec!["position".to_string(), format!("seat {}", encode_id(self.seat.as_str())), format!("top {}", self.top)];
+        lines.extend(self.hand.iter().map(|card| format!("hand {}", card)));
+        lines.extend(self.players.iter().map(|(id, cards)| format!("player {} {}", encode_id(id), cards)));
+        lines.push(format!("deck {}", self.deck));
+        lines.extend(self.moves.iter().map(|play| format!("move {}", play)));
+        lines
+    }
+
+    /// Reads one line of a position, returns false for lines that aren't part of one.
+    pub fn read(&mut self, line: &str) -> bool {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        match words.as_slice() {
+            ["position"] => *self = Position::default(),
+            ["seat", id] => match decode_id(id) {
+                Some(id) => self.seat = id,
+                None => return false,
+            },
+            ["top", card @ ..] => self.top = card.join(" "),
+            ["hand", card @ ..] => self.hand.push(card.join(" ")),
+            ["player", id, cards] => match (decode_id(id), cards.parse()) {
+                (Some(id), Ok(cards)) => self.players.push((id, cards)),
+                _ => return false,
+            },
+            ["deck", cards] => match cards.parse() {
+                Ok(cards) => self.deck = cards,
+                Err(_) => return false,
+            },
+            ["move", play @ ..] => match parse_move(play) {
+                Some(play) => self.moves.push(play),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn encode_id(id: &str) -> String {
+    let mut out = String::new();
+    for c in id.chars() {
+        if c.is_whitespace() || c == '%' {
+            for byte in c.to_string().bytes() {
+                out.push_str(format!("%{:02X}", byte).as_str());
+            }
+        }
+        else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn decode_id(word: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut rest = word.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        }
+        else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+pub fn parse_move(words: &[&str]) -> Option<Move> {
+    match words {
+        ["draw"] => Some(Move::Draw),
+        ["play", color, card] => Some(Move::Play { card: card.to_string(), color: color.to_string() }),
+        ["play", cards @ ..] => {
+            let plays = cards.split(|word| *word == "&").map(|play| match play {
+                [color, card] => Some((card.to_string(), color.to_string())),
+                _ => None,
+            }).collect::<Option<Vec<(String, String)>>>()?;
+            let card = plays[0].0.clone();
+            if plays.len() < 2 || plays.iter().any(|(other, _)| *other != card) {
+                return None
+            }
+            Some(Move::PlayMany { card, colors: plays.into_iter().map(|(_, color)| color).collect() })
+        }
+        _ => None,
+    }
+}
+
+struct Engine {
+    child: Child,
+    input: ChildStdin,
+    output: Receiver<String>,
+}
+
+impl Engine {
+    fn spawn(command: &str, args: &[String], deadline: Instant) -> Result<(Engine, Option<String>), String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Could not start {}: {}", command, err))?;
+        let input = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (lines, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break
+                };
+                if lines.send(line).is_err() {
+                    break
+                }
+            }
+        });
+        let mut engine = Engine { child, input, output };
+        engine.send(&[format!("uno {}", PROTOCOL_VERSION)])?;
+        let mut name = None;
+        loop {
+            let line = engine.recv(deadline)?;
+            if let Some(id) = line.trim().strip_prefix("id name ") {
+                name = Some(id.trim().to_string());
+            }
+            if line.trim() == "unook" {
+                return Ok((engine, name))
+            }
+        }
+    }
+
+    fn send(&mut self, lines: &[String]) -> Result<(), String> {
+        for line in lines {
+            writeln!(self.input, "{}", line).map_err(|err| format!("Engine stopped listening: {}", err))?;
+        }
+        self.input.flush().map_err(|err| format!("Engine stopped listening: {}", err))
+    }
+
+    fn recv(&self, deadline: Instant) -> Result<String, String> {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        self.output.recv_timeout(wait).map_err(|err| match err {
+            mpsc::RecvTimeoutError::Timeout => "Engine did not answer in time".to_string(),
+            mpsc::RecvTimeoutError::Disconnected => "Engine exited".to_string(),
+        })
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let _ = self.send(&["quit".to_string()]);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Asks an engine process for every move, restarting it after a crash or timeout
+/// and falling back to the first legal move whenever it can't give a usable answer.
+pub struct ExternalProcessStrategy {
+    command: String,
+    args: Vec<String>,
+    name: String,
+    time_limit: Duration,
+    restarts: usize,
+    engine: Option<Engine>,
+    last_error: Option<String>,
+}
+
+impl ExternalProcessStrategy {
+    pub fn spawn(command: &str, args: &[&str]) -> Result<Self, String> {
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        let (engine, name) = Engine::spawn(command, &args, Instant::now() + HANDSHAKE_TIMEOUT)?;
+        Ok(ExternalProcessStrategy {
+            command: command.to_string(),
+            args,
+            name: name.unwrap_or_else(|| command.to_string()),
+            time_limit: Duration::from_secs(1),
+            restarts: 0,
+            engine: Some(engine),
+            last_error: None,
+        })
+    }
+
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = limit;
+        self
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    fn ask(&mut self, position: &Position) -> Result<Move, String> {
+        // A restart shares the move's deadline, so a crashed engine never makes a move take longer.
+        let deadline = Instant::now() + self.time_limit;
+        if self.engine.is_none() {
+            if self.restarts >= MAX_RESTARTS {
+                return Err(format!("{} was restarted {} times, giving up on it", self.name, MAX_RESTARTS))
+            }
+            self.restarts += 1;
+            self.engine = Some(Engine::spawn(self.command.as_str(), &self.args, deadline)?.0);
+        }
+        let engine = self.engine.as_mut().unwrap();
+        let mut lines = position.to_lines();
+        lines.push(format!("go {}", deadline.saturating_duration_since(Instant::now()).as_millis()));
+        engine.send(&lines)?;
+        loop {
+            let line = engine.recv(deadline)?;
+            if let Some(reply) = line.trim().strip_prefix("bestmove") {
+                let words = reply.split_whitespace().collect::<Vec<&str>>();
+                return parse_move(&words)
+                    .filter(|play| position.moves.contains(play))
+                    .ok_or_else(|| format!("{} is not a legal move", reply.trim()))
+            }
+        }
+    }
+}
+
+impl Strategy for ExternalProcessStrategy {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move> {
+        let position = Position::from_game(game, player);
+        let fallback = position.moves.first().cloned()?;
+        match self.ask(&position) {
+            Ok(play) => Some(play),
+            Err(err) => {
+                self.last_error = Some(err);
+                self.engine = None;
+                Some(fallback)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> UnoGame {
+        let mut game = UnoGame::with_seed(3);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.start(&host).unwrap();
+        game
+    }
+
+    #[test]
+    fn positions_read_back_from_their_lines() {
+        let game = game();
+        let seat = game.get_curr_player().id().clone();
+        let position = Position::from_game(&game, &seat);
+        assert_eq!(position.hand.len(), 7);
+        assert_eq!(position.moves, game.legal_moves(&seat));
+        let mut read = Position::default();
+        for line in position.to_lines() {
+            assert!(read.read(line.as_str()), "{}", line);
+        }
+        assert_eq!(read, position);
+        assert!(!read.read("go 100"));
+
+        let spaced = Position { seat: "ann lee".to_string(), players: vec![("ann lee".to_string(), 3), ("100%".to_string(), 4)], ..Position::default() };
+        let lines = spaced.to_lines();
+        assert!(lines.contains(&"seat ann%20lee".to_string()) && lines.contains(&"player 100%25 4".to_string()));
+        let mut read = Position::default();
+        for line in lines {
+            assert!(read.read(line.as_str()), "{}", line);
+        }
+        assert_eq!(read, spaced);
+        assert!(!read.read("seat ann%2"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn slow_or_wrong_engines_fall_back_to_a_legal_move() {
+        let game = game();
+        let seat = game.get_curr_player().id().clone();
+        let engine = |reply: &str| format!("echo 'id name test'; echo unook; while read line; do case \"$line\" in go*) {};; esac; done", reply);
+
+        let mut drawer = ExternalProcessStrategy::spawn("sh", &["-c", engine("echo 'bestmove draw'").as_str()]).unwrap();
+        assert_eq!(drawer.name(), "test");
+        assert_eq!(drawer.choose(&game, &seat), Some(Move::Draw));
+        assert!(drawer.last_error().is_none());
+
+        let fallback = game.legal_moves(&seat).first().cloned();
+        let mut cheater = ExternalProcessStrategy::spawn("sh", &["-c", engine("echo 'bestmove play R 10'").as_str()]).unwrap();
+        assert_eq!(cheater.choose(&game, &seat), fallback);
+        assert!(cheater.last_error().is_some());
+
+        let mut sleeper = ExternalProcessStrategy::spawn("sh", &["-c", engine("sleep 5").as_str()]).unwrap()
+            .with_time_limit(Duration::from_millis(100));
+        assert_eq!(sleeper.choose(&game, &seat), fallback);
+        assert_eq!(sleeper.last_error(), Some("Engine did not answer in time"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restarts_count_against_the_time_limit() {
+        let game = game();
+        let seat = game.get_curr_player().id().clone();
+        let crasher = "sleep 0.5; echo unook; while read line; do case \"$line\" in go*) exit;; esac; done";
+        let mut engine = ExternalProcessStrategy::spawn("sh", &["-c", crasher]).unwrap()
+            .with_time_limit(Duration::from_millis(100));
+        let fallback = game.legal_moves(&seat).first().cloned();
+        assert_eq!(engine.choose(&game, &seat), fallback);
+        assert_eq!(engine.last_error(), Some("Engine exited"));
+
+        let started = Instant::now();
+        assert_eq!(engine.choose(&game, &seat), fallback);
+        assert!(started.elapsed() < Duration::from_millis(400));
+        assert_eq!(engine.last_error(), Some("Engine did not answer in time"));
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::i18n::{Locale, Msg};
+use crate::player::PlayerId;
+use crate::rules::{RuleFormat, Rules};
+use crate::uno_game::UnoGame;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Join { name: String, seat: Option<usize> },
+    Leave,
+    Kick { player: PlayerId },
+    Start,
+    Play { card: String, color: String },
+    PlayMany { card: String, colors: Vec<String> },
+    Draw,
+    Timeout,
+    /// The player on turn was removed after timing out AFK Limit times in a row.
+    RemoveAfk,
+    Uno,
+    Callout {
+        #[serde(default)]
+        caught: Vec<PlayerId>,
+    },
+    SetRule { rule: String, value: String },
+    ApplyPreset { preset: String },
+    ImportRules { input: String },
+    AddAlias { alias: String, card: String },
+    RemoveAlias { alias: String },
+    SetHost { player: PlayerId },
+    LockRules,
+    UnlockRules,
+    End,
+    VoteKick { player: PlayerId },
+    VoteRule { rule: String, value: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub at: u64,
+    pub by: PlayerId,
+    pub action: Action,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameLog {
+    pub seed: u64,
+    pub rules: String,
+    pub entries: Vec<LogEntry>,
+}
+
+impl GameLog {
+    pub(crate) fn new(seed: u64, rules: &Rules) -> Self {
+        GameLog {
+            seed,
+            rules: rules.export(RuleFormat::Toml),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(input: &str) -> Result<GameLog, String> {
+        serde_json::from_str(input).map_err(|err| format!("Invalid game log: {}", err))
+    }
+
+    pub fn turns(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.action.is_turn()).count()
+    }
+
+    pub fn turn_step(&self, turn: usize) -> usize {
+        let start = self.entries.iter().position(|entry| entry.action == Action::Start).map(|idx| idx + 1).unwrap_or(0);
+        if turn == 0 {
+            return start
+        }
+        let mut turns = 0;
+        for (idx, entry) in self.entries.iter().enumerate().skip(start) {
+            if entry.action.is_turn() {
+                turns += 1;
+                if turns == turn {
+                    return idx + 1
+                }
+            }
+        }
+        self.entries.len()
+    }
+}
+
+impl Action {
+    pub fn is_move(&self) -> bool {
+        matches!(self, Action::Play { .. } | Action::PlayMany { .. } | Action::Draw | Action::Timeout | Action::Uno | Action::Callout { .. })
+    }
+
+    pub fn is_turn(&self) -> bool {
+        matches!(self, Action::Play { .. } | Action::PlayMany { .. } | Action::Draw | Action::Timeout)
+    }
+
+    pub(crate) fn play_text(card: &str, color: &str) -> String {
+        format!("{} {}", color, card).trim().to_string()
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Join { name, .. } => write!(f, "join as {}", name),
+            Action::Leave => write!(f, "leave"),
+            Action::Kick { player } => write!(f, "kick {}", player),
+            Action::Start => write!(f, "start"),
+            Action::Play { card, color } => write!(f, "play {}", Action::play_text(card, color)),
+            Action::PlayMany { card, colors } => {
+                let cards = colors.iter().map(|color| Action::play_text(card, color)).collect::<Vec<String>>();
+                write!(f, "play {}", cards.join(" & "))
+            }
+            Action::Draw => write!(f, "draw"),
+            Action::Timeout => write!(f, "time out"),
+            Action::RemoveAfk => write!(f, "removed for being away"),
+            Action::Uno => write!(f, "UNO"),
+            Action::Callout { .. } => write!(f, "callout"),
+            Action::SetRule { rule, value } => write!(f, "set {} to {}", rule, value),
+            Action::ApplyPreset { preset } => write!(f, "use the {} preset", preset),
+            Action::ImportRules { .. } => write!(f, "import rules"),
+            Action::AddAlias { alias, card } => write!(f, "alias {} to {}", alias, card),
+            Action::RemoveAlias { alias } => write!(f, "remove alias {}", alias),
+            Action::SetHost { player } => write!(f, "make {} host", player),
+            Action::LockRules => write!(f, "lock rules"),
+            Action::UnlockRules => write!(f, "unlock rules"),
+            Action::End => write!(f, "end the game"),
+            Action::VoteKick { player } => write!(f, "vote to kick {}", player),
+            Action::VoteRule { rule, value } => write!(f, "vote to set {} to {}", rule, value),
+        }
+    }
+}
+
+impl UnoGame {
+    pub fn replay(log: &GameLog) -> Result<UnoGame, String> {
+        UnoGame::replay_to(log, log.entries.len())
+    }
+
+    pub fn replay_to(log: &GameLog, step: usize) -> Result<UnoGame, String> {
+        let rules = Rules::new().import(log.rules.as_str(), Locale::default()).map_err(|errors| errors.join("\n"))?;
+        let mut game = UnoGame::from_log(log.seed, rules);
+        for (idx, entry) in log.entries.iter().take(step).enumerate() {
+            game.set_replay_clock(Some(entry.at));
+            let res = game.apply(entry);
+            game.set_replay_clock(None);
+            if let Err(err) = res {
+                return Err(format!("Step {} ({} by {}) could not be replayed: {}", idx + 1, entry.action, entry.by, err))
+            }
+        }
+        Ok(game)
+    }
+
+    pub(crate) fn apply(&mut self, entry: &LogEntry) -> Result<String, String> {
+        let by = &entry.by;
+        match &entry.action {
+            Action::Join { name, seat } => self.add_player_at(by, name, *seat).map(|player| player.username.clone()),
+            Action::Leave => self.remove_player(by),
+            Action::Kick { player } => self.kick(by, player),
+            Action::Start => self.start(by),
+            Action::Play { card, color } => {
+                self.check_turn(by)?;
+                self.play(Action::play_text(card, color))
+            }
+            Action::PlayMany { card, colors } => {
+                self.check_turn(by)?;
+                let cards = colors.iter().map(|color| Action::play_text(card, color)).collect::<Vec<String>>();
+                self.play_many(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+            }
+            Action::Draw => {
+                self.check_turn(by)?;
+                self.draw()
+            }
+            Action::Timeout => {
+                self.check_turn(by)?;
+                self.time_out()
+            }
+            Action::RemoveAfk => {
+                self.check_turn(by)?;
+                self.remove_afk()
+            }
+            Action::Uno => self.uno(by),
+            Action::Callout { .. } => self.callout(by),
+            Action::SetRule { rule, value } => self.set_rule(by, rule, value),
+            Action::ApplyPreset { preset } => self.apply_preset(by, preset),
+            Action::ImportRules { input } => self.import_rules(by, input),
+            Action::AddAlias { alias, card } => self.add_alias(by, alias, card),
+            Action::RemoveAlias { alias } => self.remove_alias(by, alias),
+            Action::SetHost { player } => self.set_host(by, player),
+            Action::LockRules => self.lock_rules(by),
+            Action::UnlockRules => self.unlock_rules(by),
+            Action::End => self.end(by),
+            Action::VoteKick { player } => self.vote_kick(by, player),
+            Action::VoteRule { rule, value } => self.vote_rule(by, rule, value),
+        }
+    }
+
+    fn check_turn(&self, by: &PlayerId) -> Result<(), String> {
+        if self.get_curr_player().id() != by {
+            let name = self.get_player(by).map(|player| player.username.clone()).unwrap_or_else(|| by.to_string());
+            return Err(self.text_for(by, Msg::NotTheirTurn { name }))
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_out(game: &mut UnoGame) {
+        for _ in 0..5000 {
+            if game.is_over() {
+                return
+            }
+            let hand = game.get_curr_player().hand.clone();
+            let played = hand.iter().any(|card| {
+                let text = if card.wild { format!("{} R", card.id) } else { format!("{} {}", card.color, card.id) };
+                game.play(text).is_ok()
+            });
+            if !played {
+                game.draw().unwrap();
+            }
+            let ids = game.players().map(|player| player.id().clone()).collect::<Vec<PlayerId>>();
+            for id in ids {
+                let _ = game.uno(&id);
+            }
+        }
+    }
+
+    fn recorded_game() -> UnoGame {
+        let mut game = UnoGame::with_seed(42);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.add_player("carol", "Carol").unwrap();
+        game.set_rule(&host, "initial_cards", "5").unwrap();
+        game.start(&host).unwrap();
+        play_out(&mut game);
+        game
+    }
+
+    #[test]
+    fn replay_matches_final_scoreboard() {
+        let game = recorded_game();
+        assert!(game.is_over());
+        let log = GameLog::from_json(game.log().to_json().as_str()).unwrap();
+        let replayed = UnoGame::replay(&log).unwrap();
+        assert_eq!(replayed.scoreboard(), game.scoreboard());
+        assert_eq!(replayed.log(), game.log());
+    }
+
+    #[test]
+    fn replay_to_stops_at_step() {
+        let game = recorded_game();
+        let log = game.log();
+        let partial = UnoGame::replay_to(log, 6).unwrap();
+        assert_eq!(partial.log().entries, log.entries[..6]);
+        assert!(!partial.is_over());
+    }
+
+    #[test]
+    fn replays_reject_moves_out_of_turn() {
+        let mut log = recorded_game().log().clone();
+        let step = log.entries.iter().position(|entry| entry.action == Action::Draw).unwrap();
+        let game = UnoGame::replay_to(&log, step).unwrap();
+        let other = game.players().nth(1).unwrap();
+        let expected = Msg::NotTheirTurn { name: other.username().to_string() }.text(Locale::En);
+        log.entries[step].by = other.id().clone();
+        assert!(UnoGame::replay(&log).err().unwrap().ends_with(expected.as_str()));
+    }
+
+    #[test]
+    fn undo_and_redo_last_move() {
+        let mut game = UnoGame::with_seed(7);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.set_rule(&host, "undo", "on").unwrap();
+        game.start(&host).unwrap();
+        let before = game.table();
+        game.draw().unwrap();
+        let after = game.table();
+        game.undo(&host).unwrap();
+        assert_eq!(game.table(), before);
+        game.redo(&host).unwrap();
+        assert_eq!(game.table(), after);
+
+        game.draw().unwrap();
+        game.undo(&host).unwrap();
+        assert!(game.undo(&host).is_err());
+    }
+}
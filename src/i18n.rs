@@ -0,0 +1,724 @@
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Pt,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Es, Locale::Pt];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::Pt => "pt",
+        }
+    }
+
+    pub fn find(code: &str) -> Option<Locale> {
+        let code = code.trim().to_lowercase();
+        let code = code.split(['-', '_']).next().unwrap_or("");
+        Locale::ALL.iter().find(|locale| locale.code() == code).copied()
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Es => ES,
+            Locale::Pt => PT,
+        }
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+macro_rules! messages {
+    ($($name:ident $key:literal $({ $($arg:ident: $ty:ty),* })?,)*) => {
+        #[derive(Clone, Debug)]
+        pub enum Msg {
+            $($name $({ $($arg: $ty),* })?,)*
+        }
+
+        impl Msg {
+            pub const KEYS: &'static [&'static str] = &[$($key),*];
+
+            pub fn key(&self) -> &'static str {
+                match self {
+                    $(Msg::$name { .. } => $key,)*
+                }
+            }
+
+            fn args(&self) -> Vec<(&'static str, String)> {
+                match self {
+                    $(Msg::$name $({ $($arg),* })? => vec![$($((stringify!($arg), $arg.to_string())),*)?],)*
+                }
+            }
+        }
+    };
+}
+
+messages! {
+    AlreadyStarted "already_started",
+    NeedTwoPlayers "need_two_players",
+    NotEnoughCards "not_enough_cards",
+    GameStarted "game_started" { table: String },
+    ScoreboardRank "scoreboard_rank" { rank: usize, name: String },
+    LeftTheGame "left_the_game" { names: String },
+    GameLasted "game_lasted" { minutes: i64, drawn: i32 },
+    StatsLine "stats_line" { played: i32, action: i32, wild: i32, drawn: i32, penalty: i32, uno: i32, callouts: i32, false_callouts: i32, turns: i32, longest: String },
+    FinishedAfter "finished_after" { time: String },
+    AlreadyJoined "already_joined" { name: String },
+    AlreadyPlayed "already_played" { name: String },
+    GameEnded "game_ended",
+    JoinedWithCards "joined_with_cards" { name: String, count: i32 },
+    Joined "joined" { name: String },
+    NameEmpty "name_empty",
+    NameTooLong "name_too_long" { max: usize },
+    NameControl "name_control",
+    Left "left" { name: String },
+    LeftReturned "left_returned" { name: String, count: usize },
+    PlayerNotFound "player_not_found" { id: String },
+    RulesLocked "rules_locked",
+    RuleNotFound "rule_not_found" { rule: String },
+    RuleSet "rule_set" { rule: String, value: String },
+    PresetNotFound "preset_not_found" { preset: String },
+    PresetApplied "preset_applied" { preset: String },
+    RuleOnOff "rule_on_off" { rule: String },
+    RuleWholeNumber "rule_whole_number" { rule: String },
+    RuleOutOfBounds "rule_out_of_bounds" { value: String, rule: String, min: i32, max: i32 },
+    RuleChoice "rule_choice" { rule: String, choices: String },
+    RuleWrongKind "rule_wrong_kind" { rule: String, kind: String },
+    InvalidToml "invalid_toml" { error: String },
+    InvalidJson "invalid_json" { error: String },
+    BotPresetNotFound "bot_preset_not_found" { preset: String, presets: String },
+    BotJoined "bot_joined" { name: String, preset: String },
+    TimedOut "timed_out" { name: String },
+    StandIn "stand_in" { name: String },
+    RemovedAfk "removed_afk" { name: String, count: i32 },
+    BackInSeat "back_in_seat" { name: String },
+    NotAway "not_away",
+    ImportFailed "import_failed" { errors: String },
+    RulesImported "rules_imported",
+    RuleInfo "rule_info" { name: String, id: String, kind: String, value: String, desc: String },
+    OnlyHost "only_host",
+    YouAreHost "you_are_host",
+    NewHost "new_host" { name: String },
+    RulesLockedNow "rules_locked_now",
+    RulesUnlocked "rules_unlocked",
+    CannotKickSelf "cannot_kick_self",
+    NoRunningGame "no_running_game",
+    UndoDisabled "undo_disabled",
+    NothingToUndo "nothing_to_undo",
+    UndoBlocked "undo_blocked",
+    Undid "undid" { action: String, name: String },
+    NothingToRedo "nothing_to_redo",
+    VoteKickDisabled "vote_kick_disabled",
+    OnlyPlayersVote "only_players_vote",
+    CannotVoteSelf "cannot_vote_self",
+    VotePassed "vote_passed" { result: String },
+    KickVotes "kick_votes" { votes: usize, needed: usize, name: String },
+    VoteRuleDisabled "vote_rule_disabled",
+    RuleVotes "rule_votes" { votes: usize, needed: usize, rule: String, value: String },
+    NotStarted "not_started",
+    NotTheirTurn "not_their_turn" { name: String },
+    ChooseColor "choose_color" { card: String },
+    FinishedRank "finished_rank" { name: String, rank: usize },
+    Reversed "reversed",
+    SkipTurn "skip_turn" { name: String },
+    PicksUp "picks_up" { name: String, count: i32 },
+    AlsoSkip "also_skip",
+    ColorNow "color_now" { color: String },
+    PicksUpColor "picks_up_color" { name: String, color: String },
+    CannotPlay "cannot_play" { card: String },
+    CardNotFound "card_not_found" { card: String, name: String },
+    MultiPlayDisabled "multi_play_disabled",
+    MultiPlaySameFace "multi_play_same_face" { card: String },
+    DidYouMean "did_you_mean" { card: String, similar: String },
+    WhichColor "which_color" { card: String, options: String },
+    EmptyCard "empty_card",
+    UnknownWord "unknown_word" { word: String },
+    UnknownWordHint "unknown_word_hint" { word: String, hint: String },
+    MissingCard "missing_card",
+    TwoColors "two_colors" { first: String, second: String },
+    TwoCards "two_cards" { first: String, second: String },
+    AliasAdded "alias_added" { alias: String, card: String },
+    AliasRemoved "alias_removed" { alias: String },
+    AliasNotFound "alias_not_found" { alias: String },
+    AliasInvalid "alias_invalid" { alias: String, max: usize },
+    AliasUnknownCard "alias_unknown_card" { card: String },
+    AliasIsColor "alias_is_color" { alias: String, color: String },
+    AliasIsCard "alias_is_card" { alias: String, card: String },
+    AliasTaken "alias_taken" { alias: String, card: String },
+    AliasRow "alias_row" { alias: String, card: String },
+    NoAliases "no_aliases",
+    MustPlay "must_play",
+    DrewAndPlayed "drew_and_played" { card: String, result: String },
+    Drew "drew" { card: String },
+    CalloutsDisabled "callouts_disabled",
+    OnlyPlayersCallout "only_players_callout",
+    CalloutDone "callout_done",
+    CaughtNoUno "caught_no_uno" { name: String, count: i32 },
+    FalseCallout "false_callout" { count: i32 },
+    AlreadyUno "already_uno",
+    Uno "uno",
+    MoreThanOneCard "more_than_one_card",
+    LastCardPlayed "last_card_played" { card: String, scoreboard: String },
+    CardOnTable "card_on_table" { card: String, name: String },
+    TableRow "table_row" { seat: usize, name: String, count: usize },
+    GameLasting "game_lasting" { minutes: i64, drawn: i32 },
+    AlreadyPlaying "already_playing" { name: String },
+    AlreadyWatching "already_watching" { name: String },
+    StoppedWatching "stopped_watching" { name: String },
+    SpectatorNotFound "spectator_not_found" { id: String },
+    SpectatorsBehind "spectators_behind" { delay: usize },
+    WaitingForStart "waiting_for_start" { names: String },
+    Hand "hand" { cards: String, count: usize },
+    TurnStart "turn_start" { name: String },
+    PlayedWild "played_wild" { name: String, card: String, color: String },
+    Played "played" { name: String, card: String },
+    DrewCards "drew_cards" { name: String, count: i32 },
+    PickedUp "picked_up" { name: String, count: i32 },
+    SaidUno "said_uno" { name: String },
+    CalledOutNobody "called_out_nobody" { name: String },
+    CalledOut "called_out" { name: String, names: String },
+    PlayerFinished "player_finished" { name: String, rank: usize },
+    GameOver "game_over",
+    LocaleSet "locale_set" { locale: String },
+    LocaleNotFound "locale_not_found" { locale: String },
+    ColorRed "color_red",
+    ColorGreen "color_green",
+    ColorBlue "color_blue",
+    ColorYellow "color_yellow",
+    FaceSkip "face_skip",
+    FaceReverse "face_reverse",
+    FaceWild "face_wild",
+    FaceWildFour "face_wild_four",
+    CardName "card_name" { color: String, face: String },
+}
+
+impl Msg {
+    pub fn text(&self, locale: Locale) -> String {
+        let key = self.key();
+        let template = locale.catalog().iter().chain(EN.iter())
+            .find(|(id, _)| *id == key)
+            .map(|(_, template)| *template)
+            .unwrap_or(key);
+        fill(template, &self.args())
+    }
+}
+
+fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').and_then(|end| args.iter().find(|(name, _)| *name == &after[..end]).map(|(_, value)| (end, value))) {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+const EN: &[(&str, &str)] = &[
+    ("already_started", "The game has already started"),
+    ("need_two_players", "Need atleast two players to start!"),
+    ("not_enough_cards", "Did not find enough cards to start playing"),
+    ("game_started", "The game has started!\n\n{table}"),
+    ("scoreboard_rank", "{rank}. *{name}*"),
+    ("left_the_game", "Left the game: {names}"),
+    ("game_lasted", "This game lasted {minutes} minutes and {drawn} cards were drawn"),
+    ("stats_line", "{played} card(s) played ({action} action, {wild} wild), {drawn} drawn, {penalty} penalty card(s), {uno} UNO call(s), {callouts} callout(s), {false_callouts} false callout(s), {turns} turn(s), longest turn {longest}"),
+    ("finished_after", ", finished after {time}"),
+    ("already_joined", "{name} has already joined this game"),
+    ("already_played", "{name} already played in this game and cannot rejoin it"),
+    ("game_ended", "Game has ended!"),
+    ("joined_with_cards", "{name} joined the game with {count} card(s)"),
+    ("joined", "{name} joined the game"),
+    ("name_empty", "Player names cannot be empty"),
+    ("name_too_long", "Player names cannot be longer than {max} characters"),
+    ("name_control", "Player names cannot contain control characters"),
+    ("left", "{name} left the game"),
+    ("left_returned", "{name} left the game and their {count} card(s) were returned"),
+    ("player_not_found", "Player with id {id} not found"),
+    ("rules_locked", "Rules are locked while the game is running"),
+    ("rule_not_found", "Rule {rule} not found"),
+    ("rule_set", "{rule} is now {value}"),
+    ("preset_not_found", "Preset {preset} not found"),
+    ("preset_applied", "Now playing with the {preset} rules"),
+    ("rule_on_off", "Value for rule {rule} must be on or off"),
+    ("rule_whole_number", "Value for rule {rule} must be a whole number"),
+    ("rule_out_of_bounds", "Value {value} is out of bounds for rule {rule} ({min}-{max})"),
+    ("rule_choice", "Value for rule {rule} must be one of: {choices}"),
+    ("rule_wrong_kind", "Value for rule {rule} must be {kind}"),
+    ("invalid_toml", "Invalid TOML: {error}"),
+    ("invalid_json", "Invalid JSON: {error}"),
+    ("bot_preset_not_found", "There is no {preset} bot, pick one of {presets}"),
+    ("bot_joined", "{name} ({preset} bot) took a seat"),
+    ("timed_out", "{name} ran out of time, drew a card and passed"),
+    ("stand_in", "{name} ran out of time, a bot plays for them until they're back"),
+    ("removed_afk", "{name} ran out of time {count} turns in a row and was removed"),
+    ("back_in_seat", "{name} is back and playing again"),
+    ("not_away", "Nobody is playing for you right now"),
+    ("import_failed", "Could not import rules:\n{errors}"),
+    ("rules_imported", "Rules imported"),
+    ("rule_info", "*{name}* (`{id}`)\nType: {kind}\nValue: {value}\n\n{desc}"),
+    ("only_host", "Only the host can do that"),
+    ("you_are_host", "You are now the host"),
+    ("new_host", "{name} is now the host"),
+    ("rules_locked_now", "Rules are now locked"),
+    ("rules_unlocked", "Rules are now unlocked"),
+    ("cannot_kick_self", "You cannot kick yourself"),
+    ("no_running_game", "There is no running game to end"),
+    ("undo_disabled", "Undo is not permitted in this game"),
+    ("nothing_to_undo", "There is nothing to undo"),
+    ("undo_blocked", "Someone else has acted since, so this cannot be undone"),
+    ("undid", "Undid {action} by {name}"),
+    ("nothing_to_redo", "There is nothing to redo"),
+    ("vote_kick_disabled", "Vote kicks are not permitted in this game"),
+    ("only_players_vote", "Only players in the game can vote"),
+    ("cannot_vote_self", "You cannot vote to kick yourself"),
+    ("vote_passed", "The vote passed.\n{result}"),
+    ("kick_votes", "{votes}/{needed} votes to kick {name}"),
+    ("vote_rule_disabled", "Rule votes are not permitted in this game"),
+    ("rule_votes", "{votes}/{needed} votes to set {rule} to {value}"),
+    ("not_started", "The game has not started yet"),
+    ("not_their_turn", "It is not {name}'s turn"),
+    ("choose_color", "Choose a color for your {card}, e.g. {card} B"),
+    ("finished_rank", "{name} has no more cards. They finished in rank *{rank}*!"),
+    ("reversed", "Turns are now in reverse order!"),
+    ("skip_turn", "{name}, skip a turn!"),
+    ("picks_up", "{name} picks up {count}!"),
+    ("also_skip", "Also, skip a turn!"),
+    ("color_now", "The color is now {color}"),
+    ("picks_up_color", "{name} picks up! The current color is now {color}"),
+    ("cannot_play", "You cannot play this card here. Last played card was {card}"),
+    ("card_not_found", "Card {card} not found in hand, its currently {name}'s turn"),
+    ("multi_play_disabled", "Playing several cards at once is not allowed in this game"),
+    ("multi_play_same_face", "Cards played together must all match your {card}"),
+    ("did_you_mean", "Did you mean {card}? You have {similar}"),
+    ("which_color", "Which {card}? You have {options}"),
+    ("empty_card", "Which card? e.g. R 5, blue skip or wild green"),
+    ("unknown_word", "'{word}' is not a card or color"),
+    ("unknown_word_hint", "'{word}' is not a card or color, did you mean '{hint}'?"),
+    ("missing_card", "Which card? Add a number or action, e.g. red 5 or blue skip"),
+    ("two_colors", "Pick one color, not both {first} and {second}"),
+    ("two_cards", "Pick one card, not both {first} and {second}"),
+    ("alias_added", "'{alias}' now means {card}"),
+    ("alias_removed", "'{alias}' is no longer an alias"),
+    ("alias_not_found", "There is no alias '{alias}'"),
+    ("alias_invalid", "'{alias}' can't be an alias, use a single word of at most {max} characters"),
+    ("alias_unknown_card", "{card} is not a card, e.g. 5, skip, reverse, +2, wild or wild+4"),
+    ("alias_is_color", "'{alias}' already means the color {color}"),
+    ("alias_is_card", "'{alias}' already means {card}"),
+    ("alias_taken", "'{alias}' is already an alias for {card}, remove it first"),
+    ("alias_row", "{alias} = {card}"),
+    ("no_aliases", "There are no card aliases in this game"),
+    ("must_play", "You must play a card if able."),
+    ("drew_and_played", "You drew and played a {card}\n{result}"),
+    ("drew", "You drew a {card}"),
+    ("callouts_disabled", "Callouts are not permitted in this game"),
+    ("only_players_callout", "Only players in the game can call someone out"),
+    ("callout_done", "A callout was already performed in this turn!"),
+    ("caught_no_uno", "{name} you did not say UNO! Pick up {count}"),
+    ("false_callout", "There was no one to call out! Pick up {count}"),
+    ("already_uno", "You already said UNO!"),
+    ("uno", "UNO!"),
+    ("more_than_one_card", "You have more than 1 card!"),
+    ("last_card_played", "A {card} was the last card played!\n\n{scoreboard}"),
+    ("card_on_table", "A {card} has been played!\nIt is currently {name}'s turn!"),
+    ("table_row", "{seat}. {name} - {count} cards"),
+    ("game_lasting", "This game has lasted {minutes} minutes and {drawn} cards have been drawn"),
+    ("already_playing", "{name} is already playing in this game"),
+    ("already_watching", "{name} is already watching this game"),
+    ("stopped_watching", "{name} stopped watching"),
+    ("spectator_not_found", "Spectator with id {id} not found"),
+    ("spectators_behind", "Spectators are {delay} turn(s) behind, check back once the game has gone on a little longer"),
+    ("waiting_for_start", "Waiting for the game to start. Players: {names}"),
+    ("hand", "Here is your hand:\n\n{cards}\n\nYou currently have {count} card(s)."),
+    ("turn_start", "It is now {name}'s turn"),
+    ("played_wild", "{name} played a {card} and chose {color}"),
+    ("played", "{name} played a {card}"),
+    ("drew_cards", "{name} drew {count} card(s)"),
+    ("picked_up", "{name} picked up {count} card(s)"),
+    ("said_uno", "{name} said UNO!"),
+    ("called_out_nobody", "{name} called out nobody"),
+    ("called_out", "{name} called out {names}"),
+    ("player_finished", "{name} finished in rank {rank}"),
+    ("game_over", "The game is over!"),
+    ("locale_set", "Messages will now be shown in {locale}"),
+    ("locale_not_found", "Language {locale} is not available"),
+    ("color_red", "Red"),
+    ("color_green", "Green"),
+    ("color_blue", "Blue"),
+    ("color_yellow", "Yellow"),
+    ("face_skip", "SKIP"),
+    ("face_reverse", "REVERSE"),
+    ("face_wild", "WILD"),
+    ("face_wild_four", "WILD+4"),
+    ("card_name", "{color} {face}"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("already_started", "La partida ya ha comenzado"),
+    ("need_two_players", "¡Se necesitan al menos dos jugadores para empezar!"),
+    ("not_enough_cards", "No hay suficientes cartas para empezar a jugar"),
+    ("game_started", "¡La partida ha comenzado!\n\n{table}"),
+    ("scoreboard_rank", "{rank}. *{name}*"),
+    ("left_the_game", "Abandonaron la partida: {names}"),
+    ("game_lasted", "Esta partida duró {minutes} minutos y se robaron {drawn} cartas"),
+    ("stats_line", "{played} carta(s) jugada(s) ({action} de acción, {wild} comodines), {drawn} robada(s), {penalty} carta(s) de penalización, {uno} UNO cantado(s), {callouts} acusación(es), {false_callouts} acusación(es) falsa(s), {turns} turno(s), turno más largo {longest}"),
+    ("finished_after", ", terminó tras {time}"),
+    ("already_joined", "{name} ya se ha unido a esta partida"),
+    ("already_played", "{name} ya jugó en esta partida y no puede volver a unirse"),
+    ("game_ended", "¡La partida ha terminado!"),
+    ("joined_with_cards", "{name} se unió a la partida con {count} carta(s)"),
+    ("joined", "{name} se unió a la partida"),
+    ("name_empty", "El nombre del jugador no puede estar vacío"),
+    ("name_too_long", "El nombre del jugador no puede tener más de {max} caracteres"),
+    ("name_control", "El nombre del jugador no puede contener caracteres de control"),
+    ("left", "{name} abandonó la partida"),
+    ("left_returned", "{name} abandonó la partida y se devolvieron sus {count} carta(s)"),
+    ("player_not_found", "No se encontró al jugador con id {id}"),
+    ("rules_locked", "Las reglas están bloqueadas mientras la partida está en curso"),
+    ("rule_not_found", "No se encontró la regla {rule}"),
+    ("rule_set", "{rule} ahora es {value}"),
+    ("preset_not_found", "No se encontró el conjunto de reglas {preset}"),
+    ("preset_applied", "Ahora se juega con las reglas {preset}"),
+    ("rule_on_off", "El valor de la regla {rule} debe ser on u off"),
+    ("rule_whole_number", "El valor de la regla {rule} debe ser un número entero"),
+    ("rule_out_of_bounds", "El valor {value} está fuera de los límites de la regla {rule} ({min}-{max})"),
+    ("rule_choice", "El valor de la regla {rule} debe ser uno de: {choices}"),
+    ("rule_wrong_kind", "El valor de la regla {rule} debe ser {kind}"),
+    ("invalid_toml", "TOML no válido: {error}"),
+    ("invalid_json", "JSON no válido: {error}"),
+    ("bot_preset_not_found", "No existe el bot {preset}, elige uno de {presets}"),
+    ("bot_joined", "{name} (bot {preset}) se sentó a la mesa"),
+    ("timed_out", "A {name} se le acabó el tiempo, robó una carta y pasó"),
+    ("stand_in", "A {name} se le acabó el tiempo, un bot juega por él hasta que vuelva"),
+    ("removed_afk", "A {name} se le acabó el tiempo {count} turnos seguidos y fue eliminado"),
+    ("back_in_seat", "{name} volvió y está jugando otra vez"),
+    ("not_away", "Nadie está jugando por ti ahora mismo"),
+    ("import_failed", "No se pudieron importar las reglas:\n{errors}"),
+    ("rules_imported", "Reglas importadas"),
+    ("rule_info", "*{name}* (`{id}`)\nTipo: {kind}\nValor: {value}\n\n{desc}"),
+    ("only_host", "Solo el anfitrión puede hacer eso"),
+    ("you_are_host", "Ahora eres el anfitrión"),
+    ("new_host", "{name} es ahora el anfitrión"),
+    ("rules_locked_now", "Las reglas están bloqueadas"),
+    ("rules_unlocked", "Las reglas están desbloqueadas"),
+    ("cannot_kick_self", "No puedes expulsarte a ti mismo"),
+    ("no_running_game", "No hay ninguna partida en curso que terminar"),
+    ("undo_disabled", "No se permite deshacer en esta partida"),
+    ("nothing_to_undo", "No hay nada que deshacer"),
+    ("undo_blocked", "Alguien más ha jugado desde entonces, así que no se puede deshacer"),
+    ("undid", "Se deshizo {action} de {name}"),
+    ("nothing_to_redo", "No hay nada que rehacer"),
+    ("vote_kick_disabled", "No se permiten votaciones de expulsión en esta partida"),
+    ("only_players_vote", "Solo los jugadores de la partida pueden votar"),
+    ("cannot_vote_self", "No puedes votar para expulsarte a ti mismo"),
+    ("vote_passed", "La votación fue aprobada.\n{result}"),
+    ("kick_votes", "{votes}/{needed} votos para expulsar a {name}"),
+    ("vote_rule_disabled", "No se permiten votaciones de reglas en esta partida"),
+    ("rule_votes", "{votes}/{needed} votos para cambiar {rule} a {value}"),
+    ("not_started", "La partida aún no ha comenzado"),
+    ("not_their_turn", "No es el turno de {name}"),
+    ("choose_color", "Elige un color para tu {card}, p. ej. {card} B"),
+    ("finished_rank", "{name} no tiene más cartas. ¡Terminó en el puesto *{rank}*!"),
+    ("reversed", "¡Ahora los turnos van en orden inverso!"),
+    ("skip_turn", "¡{name}, pierdes un turno!"),
+    ("picks_up", "¡{name} roba {count}!"),
+    ("also_skip", "¡Y además pierde un turno!"),
+    ("color_now", "Ahora el color es {color}"),
+    ("picks_up_color", "¡{name} roba! Ahora el color es {color}"),
+    ("cannot_play", "No puedes jugar esta carta aquí. La última carta jugada fue {card}"),
+    ("card_not_found", "La carta {card} no está en tu mano, es el turno de {name}"),
+    ("multi_play_disabled", "No se pueden jugar varias cartas a la vez en esta partida"),
+    ("multi_play_same_face", "Las cartas jugadas juntas deben coincidir con tu {card}"),
+    ("did_you_mean", "¿Quisiste decir {card}? Tienes {similar}"),
+    ("which_color", "¿Qué {card}? Tienes {options}"),
+    ("empty_card", "¿Qué carta? p. ej. R 5, blue skip o wild green"),
+    ("unknown_word", "'{word}' no es una carta ni un color"),
+    ("unknown_word_hint", "'{word}' no es una carta ni un color, ¿quisiste decir '{hint}'?"),
+    ("missing_card", "¿Qué carta? Añade un número o una acción, p. ej. red 5 o blue skip"),
+    ("two_colors", "Elige un solo color, no {first} y {second}"),
+    ("two_cards", "Elige una sola carta, no {first} y {second}"),
+    ("alias_added", "'{alias}' ahora significa {card}"),
+    ("alias_removed", "'{alias}' ya no es un alias"),
+    ("alias_not_found", "No existe el alias '{alias}'"),
+    ("alias_invalid", "'{alias}' no puede ser un alias, usa una sola palabra de como máximo {max} caracteres"),
+    ("alias_unknown_card", "{card} no es una carta, p. ej. 5, skip, reverse, +2, wild o wild+4"),
+    ("alias_is_color", "'{alias}' ya significa el color {color}"),
+    ("alias_is_card", "'{alias}' ya significa {card}"),
+    ("alias_taken", "'{alias}' ya es un alias de {card}, quítalo primero"),
+    ("alias_row", "{alias} = {card}"),
+    ("no_aliases", "No hay alias de cartas en esta partida"),
+    ("must_play", "Debes jugar una carta si puedes."),
+    ("drew_and_played", "Robaste y jugaste un {card}\n{result}"),
+    ("drew", "Robaste un {card}"),
+    ("callouts_disabled", "No se permiten acusaciones en esta partida"),
+    ("only_players_callout", "Solo los jugadores de la partida pueden acusar a alguien"),
+    ("callout_done", "¡Ya se hizo una acusación en este turno!"),
+    ("caught_no_uno", "¡{name}, no dijiste UNO! Roba {count}"),
+    ("false_callout", "¡No había nadie a quien acusar! Roba {count}"),
+    ("already_uno", "¡Ya dijiste UNO!"),
+    ("uno", "¡UNO!"),
+    ("more_than_one_card", "¡Tienes más de 1 carta!"),
+    ("last_card_played", "¡Un {card} fue la última carta jugada!\n\n{scoreboard}"),
+    ("card_on_table", "¡Se ha jugado un {card}!\n¡Es el turno de {name}!"),
+    ("table_row", "{seat}. {name} - {count} cartas"),
+    ("game_lasting", "Esta partida lleva {minutes} minutos y se han robado {drawn} cartas"),
+    ("already_playing", "{name} ya está jugando en esta partida"),
+    ("already_watching", "{name} ya está viendo esta partida"),
+    ("stopped_watching", "{name} dejó de mirar"),
+    ("spectator_not_found", "No se encontró al espectador con id {id}"),
+    ("spectators_behind", "Los espectadores van {delay} turno(s) por detrás, vuelve cuando la partida haya avanzado un poco más"),
+    ("waiting_for_start", "Esperando a que empiece la partida. Jugadores: {names}"),
+    ("hand", "Esta es tu mano:\n\n{cards}\n\nTienes {count} carta(s)."),
+    ("turn_start", "Ahora es el turno de {name}"),
+    ("played_wild", "{name} jugó un {card} y eligió {color}"),
+    ("played", "{name} jugó un {card}"),
+    ("drew_cards", "{name} robó {count} carta(s)"),
+    ("picked_up", "{name} recogió {count} carta(s)"),
+    ("said_uno", "¡{name} dijo UNO!"),
+    ("called_out_nobody", "{name} no acusó a nadie"),
+    ("called_out", "{name} acusó a {names}"),
+    ("player_finished", "{name} terminó en el puesto {rank}"),
+    ("game_over", "¡La partida ha terminado!"),
+    ("locale_set", "Los mensajes se mostrarán ahora en {locale}"),
+    ("locale_not_found", "El idioma {locale} no está disponible"),
+    ("color_red", "Rojo"),
+    ("color_green", "Verde"),
+    ("color_blue", "Azul"),
+    ("color_yellow", "Amarillo"),
+    ("face_skip", "SALTA"),
+    ("face_reverse", "REVERSA"),
+    ("face_wild", "COMODÍN"),
+    ("face_wild_four", "COMODÍN+4"),
+    ("card_name", "{face} {color}"),
+];
+
+const PT: &[(&str, &str)] = &[
+    ("already_started", "A partida já começou"),
+    ("need_two_players", "São necessários pelo menos dois jogadores para começar!"),
+    ("not_enough_cards", "Não há cartas suficientes para começar a jogar"),
+    ("game_started", "A partida começou!\n\n{table}"),
+    ("scoreboard_rank", "{rank}. *{name}*"),
+    ("left_the_game", "Saíram da partida: {names}"),
+    ("game_lasted", "Esta partida durou {minutes} minutos e {drawn} cartas foram compradas"),
+    ("stats_line", "{played} carta(s) jogada(s) ({action} de ação, {wild} coringas), {drawn} comprada(s), {penalty} carta(s) de penalidade, {uno} UNO(s) dito(s), {callouts} denúncia(s), {false_callouts} denúncia(s) falsa(s), {turns} turno(s), turno mais longo {longest}"),
+    ("finished_after", ", terminou após {time}"),
+    ("already_joined", "{name} já entrou nesta partida"),
+    ("already_played", "{name} já jogou nesta partida e não pode voltar a entrar"),
+    ("game_ended", "A partida terminou!"),
+    ("joined_with_cards", "{name} entrou na partida com {count} carta(s)"),
+    ("joined", "{name} entrou na partida"),
+    ("name_empty", "O nome do jogador não pode ficar vazio"),
+    ("name_too_long", "O nome do jogador não pode ter mais de {max} caracteres"),
+    ("name_control", "O nome do jogador não pode conter caracteres de controle"),
+    ("left", "{name} saiu da partida"),
+    ("left_returned", "{name} saiu da partida e suas {count} carta(s) foram devolvidas"),
+    ("player_not_found", "Jogador com id {id} não encontrado"),
+    ("rules_locked", "As regras ficam bloqueadas enquanto a partida está em andamento"),
+    ("rule_not_found", "Regra {rule} não encontrada"),
+    ("rule_set", "{rule} agora é {value}"),
+    ("preset_not_found", "Conjunto de regras {preset} não encontrado"),
+    ("preset_applied", "Agora jogando com as regras {preset}"),
+    ("rule_on_off", "O valor da regra {rule} deve ser on ou off"),
+    ("rule_whole_number", "O valor da regra {rule} deve ser um número inteiro"),
+    ("rule_out_of_bounds", "O valor {value} está fora dos limites da regra {rule} ({min}-{max})"),
+    ("rule_choice", "O valor da regra {rule} deve ser um de: {choices}"),
+    ("rule_wrong_kind", "O valor da regra {rule} deve ser {kind}"),
+    ("invalid_toml", "TOML inválido: {error}"),
+    ("invalid_json", "JSON inválido: {error}"),
+    ("bot_preset_not_found", "Não existe o bot {preset}, escolha um de {presets}"),
+    ("bot_joined", "{name} (bot {preset}) sentou-se à mesa"),
+    ("timed_out", "O tempo de {name} acabou, comprou uma carta e passou"),
+    ("stand_in", "O tempo de {name} acabou, um bot joga por ele até que volte"),
+    ("removed_afk", "O tempo de {name} acabou {count} turnos seguidos e foi removido"),
+    ("back_in_seat", "{name} voltou e está jogando de novo"),
+    ("not_away", "Ninguém está jogando por você agora"),
+    ("import_failed", "Não foi possível importar as regras:\n{errors}"),
+    ("rules_imported", "Regras importadas"),
+    ("rule_info", "*{name}* (`{id}`)\nTipo: {kind}\nValor: {value}\n\n{desc}"),
+    ("only_host", "Só o anfitrião pode fazer isso"),
+    ("you_are_host", "Agora você é o anfitrião"),
+    ("new_host", "{name} agora é o anfitrião"),
+    ("rules_locked_now", "As regras agora estão bloqueadas"),
+    ("rules_unlocked", "As regras agora estão desbloqueadas"),
+    ("cannot_kick_self", "Você não pode expulsar a si mesmo"),
+    ("no_running_game", "Não há nenhuma partida em andamento para encerrar"),
+    ("undo_disabled", "Desfazer não é permitido nesta partida"),
+    ("nothing_to_undo", "Não há nada para desfazer"),
+    ("undo_blocked", "Outra pessoa já jogou desde então, então isso não pode ser desfeito"),
+    ("undid", "{action} de {name} foi desfeito"),
+    ("nothing_to_redo", "Não há nada para refazer"),
+    ("vote_kick_disabled", "Votações de expulsão não são permitidas nesta partida"),
+    ("only_players_vote", "Só jogadores da partida podem votar"),
+    ("cannot_vote_self", "Você não pode votar para expulsar a si mesmo"),
+    ("vote_passed", "A votação foi aprovada.\n{result}"),
+    ("kick_votes", "{votes}/{needed} votos para expulsar {name}"),
+    ("vote_rule_disabled", "Votações de regras não são permitidas nesta partida"),
+    ("rule_votes", "{votes}/{needed} votos para mudar {rule} para {value}"),
+    ("not_started", "A partida ainda não começou"),
+    ("not_their_turn", "Não é a vez de {name}"),
+    ("choose_color", "Escolha uma cor para o seu {card}, por exemplo {card} B"),
+    ("finished_rank", "{name} não tem mais cartas. Terminou na posição *{rank}*!"),
+    ("reversed", "Os turnos agora estão em ordem inversa!"),
+    ("skip_turn", "{name}, perca a vez!"),
+    ("picks_up", "{name} compra {count}!"),
+    ("also_skip", "E também perde a vez!"),
+    ("color_now", "A cor agora é {color}"),
+    ("picks_up_color", "{name} compra! A cor atual agora é {color}"),
+    ("cannot_play", "Você não pode jogar esta carta aqui. A última carta jogada foi {card}"),
+    ("card_not_found", "A carta {card} não está na mão, é a vez de {name}"),
+    ("multi_play_disabled", "Não é permitido jogar várias cartas de uma vez nesta partida"),
+    ("multi_play_same_face", "As cartas jogadas juntas devem combinar com o seu {card}"),
+    ("did_you_mean", "Você quis dizer {card}? Você tem {similar}"),
+    ("which_color", "Qual {card}? Você tem {options}"),
+    ("empty_card", "Qual carta? por exemplo R 5, blue skip ou wild green"),
+    ("unknown_word", "'{word}' não é uma carta nem uma cor"),
+    ("unknown_word_hint", "'{word}' não é uma carta nem uma cor, você quis dizer '{hint}'?"),
+    ("missing_card", "Qual carta? Adicione um número ou uma ação, por exemplo red 5 ou blue skip"),
+    ("two_colors", "Escolha uma só cor, não {first} e {second}"),
+    ("two_cards", "Escolha uma só carta, não {first} e {second}"),
+    ("alias_added", "'{alias}' agora significa {card}"),
+    ("alias_removed", "'{alias}' não é mais um alias"),
+    ("alias_not_found", "Não existe o alias '{alias}'"),
+    ("alias_invalid", "'{alias}' não pode ser um alias, use uma única palavra de no máximo {max} caracteres"),
+    ("alias_unknown_card", "{card} não é uma carta, por exemplo 5, skip, reverse, +2, wild ou wild+4"),
+    ("alias_is_color", "'{alias}' já significa a cor {color}"),
+    ("alias_is_card", "'{alias}' já significa {card}"),
+    ("alias_taken", "'{alias}' já é um alias de {card}, remova-o primeiro"),
+    ("alias_row", "{alias} = {card}"),
+    ("no_aliases", "Não há aliases de cartas nesta partida"),
+    ("must_play", "Você deve jogar uma carta se puder."),
+    ("drew_and_played", "Você comprou e jogou um {card}\n{result}"),
+    ("drew", "Você comprou um {card}"),
+    ("callouts_disabled", "Denúncias não são permitidas nesta partida"),
+    ("only_players_callout", "Só jogadores da partida podem denunciar alguém"),
+    ("callout_done", "Uma denúncia já foi feita neste turno!"),
+    ("caught_no_uno", "{name}, você não disse UNO! Compre {count}"),
+    ("false_callout", "Não havia ninguém para denunciar! Compre {count}"),
+    ("already_uno", "Você já disse UNO!"),
+    ("uno", "UNO!"),
+    ("more_than_one_card", "Você tem mais de 1 carta!"),
+    ("last_card_played", "Um {card} foi a última carta jogada!\n\n{scoreboard}"),
+    ("card_on_table", "Um {card} foi jogado!\nAgora é a vez de {name}!"),
+    ("table_row", "{seat}. {name} - {count} cartas"),
+    ("game_lasting", "Esta partida já dura {minutes} minutos e {drawn} cartas foram compradas"),
+    ("already_playing", "{name} já está jogando nesta partida"),
+    ("already_watching", "{name} já está assistindo esta partida"),
+    ("stopped_watching", "{name} parou de assistir"),
+    ("spectator_not_found", "Espectador com id {id} não encontrado"),
+    ("spectators_behind", "Os espectadores estão {delay} turno(s) atrasados, volte quando a partida tiver avançado um pouco mais"),
+    ("waiting_for_start", "Aguardando a partida começar. Jogadores: {names}"),
+    ("hand", "Aqui está a sua mão:\n\n{cards}\n\nVocê tem {count} carta(s)."),
+    ("turn_start", "Agora é a vez de {name}"),
+    ("played_wild", "{name} jogou um {card} e escolheu {color}"),
+    ("played", "{name} jogou um {card}"),
+    ("drew_cards", "{name} comprou {count} carta(s)"),
+    ("picked_up", "{name} pegou {count} carta(s)"),
+    ("said_uno", "{name} disse UNO!"),
+    ("called_out_nobody", "{name} não denunciou ninguém"),
+    ("called_out", "{name} denunciou {names}"),
+    ("player_finished", "{name} terminou na posição {rank}"),
+    ("game_over", "A partida terminou!"),
+    ("locale_set", "As mensagens agora serão mostradas em {locale}"),
+    ("locale_not_found", "O idioma {locale} não está disponível"),
+    ("color_red", "Vermelho"),
+    ("color_green", "Verde"),
+    ("color_blue", "Azul"),
+    ("color_yellow", "Amarelo"),
+    ("face_skip", "BLOQUEIO"),
+    ("face_reverse", "INVERTER"),
+    ("face_wild", "CORINGA"),
+    ("face_wild_four", "CORINGA+4"),
+    ("card_name", "{face} {color}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::player::PlayerId;
+    use crate::uno_game::UnoGame;
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names = template.split('{').skip(1).filter_map(|part| part.split_once('}').map(|(name, _)| name)).collect::<Vec<&str>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn every_catalog_covers_every_key() {
+        for locale in Locale::ALL {
+            let catalog = locale.catalog();
+            for key in Msg::KEYS {
+                let template = catalog.iter().find(|(id, _)| id == key).map(|(_, template)| *template);
+                let english = EN.iter().find(|(id, _)| id == key).map(|(_, template)| *template);
+                assert!(template.is_some(), "{} is missing {}", locale, key);
+                assert_eq!(placeholders(template.unwrap()), placeholders(english.unwrap()), "{} has different arguments for {}", locale, key);
+            }
+            assert_eq!(catalog.len(), Msg::KEYS.len(), "{} has unknown keys", locale);
+        }
+    }
+
+    #[test]
+    fn fills_typed_arguments() {
+        let msg = Msg::KickVotes { votes: 1, needed: 2, name: "{votes}".to_string() };
+        assert_eq!(msg.text(Locale::En), "1/2 votes to kick {votes}");
+        assert_eq!(msg.text(Locale::Es), "1/2 votos para expulsar a {votes}");
+        assert_eq!(Locale::find("pt-BR"), Some(Locale::Pt));
+    }
+
+    #[test]
+    fn players_get_their_own_locale() {
+        let mut game = UnoGame::with_seed(3);
+        let host = PlayerId::from("alice");
+        let bob = PlayerId::from("bob");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.set_locale(&host, "pt").unwrap();
+        game.set_player_locale(&bob, "es").unwrap();
+        assert_eq!(game.play("R 1".to_string()).unwrap_err(), "A partida ainda não começou");
+        game.start(&host).unwrap();
+        assert_eq!(game.uno(&bob).unwrap_err(), "¡Tienes más de 1 carta!");
+        assert_eq!(game.uno(&host).unwrap_err(), "Você tem mais de 1 carta!");
+
+        let drawer = game.get_curr_player().id().clone();
+        let locale = game.locale_of(&drawer);
+        let drawn = game.draw().unwrap();
+        let card = game.get_player(&drawer).unwrap().cards().iter().find(|card| drawn.contains(card.text(locale).as_str()));
+        assert!(card.is_some(), "{}", drawn);
+        assert!(!["Red", "Green", "Blue", "Yellow"].iter().any(|color| drawn.contains(color)), "{}", drawn);
+    }
+
+    #[test]
+    fn names_cards_in_each_locale() {
+        assert_eq!(Card::new("5".to_string(), "R", 0).text(Locale::Es), "5 Rojo");
+        assert_eq!(Card::new("SKIP".to_string(), "Y", 0).text(Locale::Pt), "BLOQUEIO Amarelo");
+        assert_eq!(Card::new("WILD+4".to_string(), "wild", 0).text(Locale::Es), "COMODÍN+4");
+        assert_eq!(Card::new("WILD".to_string(), "B", 0).text(Locale::En), "Blue WILD");
+        assert_eq!(Card::new("+2".to_string(), "G", 0).to_string(), "Green +2");
+    }
+}
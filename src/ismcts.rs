@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng, SeedableRng};
+use crate::card::Card;
+use crate::history::Action;
+use crate::i18n::Locale;
+use crate::moves::Move;
+use crate::player::PlayerId;
+use crate::rules::{RuleKey, Rules};
+use crate::strategy::Strategy;
+use crate::uno_game::UnoGame;
+
+const MAX_PLAYOUT: usize = 200;
+
+#[derive(Default)]
+struct Void {
+    colors: HashSet<String>,
+    ids: HashSet<String>,
+    kept: usize,
+}
+
+impl Void {
+    fn allows(&self, card: &Card) -> bool {
+        !self.ids.contains(card.id()) && (card.is_wild() || !self.colors.contains(card.color()))
+    }
+}
+
+struct Node {
+    parent: Option<usize>,
+    mover: Option<PlayerId>,
+    play: Option<Move>,
+    children: Vec<usize>,
+    visits: f64,
+    reward: f64,
+    available: f64,
+}
+
+impl Node {
+    fn new(parent: Option<usize>, mover: Option<PlayerId>, play: Option<Move>) -> Self {
+        Node {
+            parent,
+            mover,
+            play,
+            children: Vec::new(),
+            visits: 0.0,
+            reward: 0.0,
+            available: 1.0,
+        }
+    }
+}
+
+pub struct Ismcts {
+    iterations: usize,
+    time_limit: Option<Duration>,
+    exploration: f64,
+    rng: StdRng,
+}
+
+impl Ismcts {
+    pub fn new(iterations: usize) -> Self {
+        Ismcts {
+            iterations,
+            time_limit: None,
+            exploration: 0.7,
+            rng: StdRng::seed_from_u64(thread_rng().gen()),
+        }
+    }
+
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    fn search(&mut self, game: &UnoGame, me: &PlayerId) -> Option<Move> {
+        let moves = game.legal_moves(me);
+        if moves.len() <= 1 {
+            return moves.into_iter().next()
+        }
+        let voids = voids(game, me);
+        let base = game.sandbox(&mut self.rng);
+        let mut nodes = vec![Node::new(None, None, None)];
+        let started = Instant::now();
+        for iteration in 0..self.iterations {
+            if self.time_limit.is_some_and(|limit| iteration > 0 && started.elapsed() >= limit) {
+                break
+            }
+            let mut sim = determinize(&base, me, &voids, &mut self.rng);
+            let mut node = 0;
+            while !sim.is_over() {
+                let mover = sim.get_curr_player().id().clone();
+                let legal = sim.legal_moves(&mover);
+                let untried = legal.iter()
+                    .filter(|play| !nodes[node].children.iter().any(|child| nodes[*child].play.as_ref() == Some(*play)))
+                    .collect::<Vec<&Move>>();
+                if let Some(play) = untried.choose(&mut self.rng).map(|play| (*play).clone()) {
+                    if sim.make_move(&play).is_err() {
+                        break
+                    }
+                    nodes.push(Node::new(Some(node), Some(mover), Some(play)));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    node = child;
+                    break
+                }
+                let available = nodes[node].children.iter()
+                    .copied()
+                    .filter(|child| nodes[*child].play.as_ref().is_some_and(|play| legal.contains(play)))
+                    .collect::<Vec<usize>>();
+                for child in &available {
+                    nodes[*child].available += 1.0;
+                }
+                let score = |child: &usize| {
+                    let child = &nodes[*child];
+                    child.reward / child.visits + self.exploration * (child.available.ln() / child.visits).sqrt()
+                };
+                let Some(best) = available.iter().copied().max_by(|a, b| score(a).total_cmp(&score(b))) else {
+                    break
+                };
+                if sim.make_move(nodes[best].play.as_ref().unwrap()).is_err() {
+                    break
+                }
+                node = best;
+            }
+            playout(&mut sim, &mut self.rng);
+            let rewards = rewards(&sim);
+            let mut current = Some(node);
+            while let Some(idx) = current {
+                let node = &mut nodes[idx];
+                node.visits += 1.0;
+                if let Some(mover) = &node.mover {
+                    node.reward += rewards.get(mover).copied().unwrap_or(0.0);
+                }
+                current = node.parent;
+            }
+        }
+        nodes[0].children.iter()
+            .max_by(|a, b| nodes[**a].visits.total_cmp(&nodes[**b].visits))
+            .and_then(|child| nodes[*child].play.clone())
+            .or_else(|| moves.into_iter().next())
+    }
+}
+
+impl Strategy for Ismcts {
+    fn name(&self) -> String {
+        format!("ismcts({})", self.iterations)
+    }
+
+    fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move> {
+        self.search(game, player)
+    }
+}
+
+fn voids(game: &UnoGame, viewer: &PlayerId) -> HashMap<PlayerId, Void> {
+    let mut voids: HashMap<PlayerId, Void> = HashMap::new();
+    let log = game.log();
+    let Ok(rules) = Rules::new().import(log.rules.as_str(), Locale::default()) else {
+        return voids
+    };
+    let mut replay = UnoGame::from_log(log.seed, rules);
+    for entry in &log.entries {
+        if entry.by != *viewer && replay.is_started() && !replay.is_over() {
+            match &entry.action {
+                Action::Draw if replay.rules().bool(RuleKey::MustPlay) && !replay.get_curr_card().color().is_empty() => {
+                    let top = replay.get_curr_card();
+                    let held = replay.get_player(&entry.by).map(|player| player.cards().len()).unwrap_or(0);
+                    let void = voids.entry(entry.by.clone()).or_default();
+                    void.kept = if void.colors.is_empty() { held } else { void.kept.min(held) };
+                    void.colors.insert(top.color().to_string());
+                    if !top.is_wild() {
+                        void.ids.insert(top.id().to_string());
+                    }
+                    void.ids.extend(["WILD".to_string(), "WILD+4".to_string()]);
+                }
+                Action::Play { .. } => {
+                    if let Some(void) = voids.get_mut(&entry.by) {
+                        void.kept = void.kept.saturating_sub(1);
+                    }
+                }
+                Action::PlayMany { colors, .. } => {
+                    if let Some(void) = voids.get_mut(&entry.by) {
+                        void.kept = void.kept.saturating_sub(colors.len());
+                    }
+                }
+                _ => {}
+            }
+        }
+        replay.set_replay_clock(Some(entry.at));
+        let res = replay.apply(entry);
+        replay.set_replay_clock(None);
+        if res.is_err() {
+            break
+        }
+    }
+    voids
+}
+
+fn determinize(base: &UnoGame, viewer: &PlayerId, voids: &HashMap<PlayerId, Void>, rng: &mut StdRng) -> UnoGame {
+    let mut pool = base.hidden_cards(viewer);
+    pool.shuffle(rng);
+    let mut seats = base.players()
+        .filter(|player| player.id() != viewer)
+        .map(|player| (player.id().clone(), player.cards().len()))
+        .collect::<Vec<(PlayerId, usize)>>();
+    seats.sort_by_key(|(id, _)| !voids.contains_key(id));
+    let mut hands = Vec::new();
+    for (id, count) in seats {
+        let mut hand = Vec::new();
+        if let Some(void) = voids.get(&id) {
+            let wanted = void.kept.min(count);
+            let mut idx = 0;
+            while hand.len() < wanted && idx < pool.len() {
+                if void.allows(&pool[idx]) {
+                    hand.push(pool.remove(idx));
+                }
+                else {
+                    idx += 1;
+                }
+            }
+        }
+        while hand.len() < count {
+            match pool.pop() {
+                Some(card) => hand.push(card),
+                None => break,
+            }
+        }
+        hands.push((id, hand));
+    }
+    let mut game = base.clone();
+    game.set_hidden(hands, pool);
+    game
+}
+
+fn playout(game: &mut UnoGame, rng: &mut StdRng) {
+    for _ in 0..MAX_PLAYOUT {
+        if game.is_over() {
+            return
+        }
+        let id = game.get_curr_player().id().clone();
+        let moves = game.legal_moves(&id);
+        let plays = moves.iter().filter(|play| **play != Move::Draw).collect::<Vec<&Move>>();
+        let Some(play) = plays.choose(rng).copied().or(moves.first()).cloned() else {
+            return
+        };
+        if game.make_move(&play).is_err() {
+            return
+        }
+    }
+}
+
+fn rewards(game: &UnoGame) -> HashMap<PlayerId, f64> {
+    let mut order = game.standings().iter().map(|player| player.id().clone()).collect::<Vec<PlayerId>>();
+    let mut rest = game.players().map(|player| (player.cards().len(), player.id().clone())).collect::<Vec<(usize, PlayerId)>>();
+    rest.sort();
+    order.extend(rest.into_iter().map(|(_, id)| id));
+    let last = order.len().saturating_sub(1).max(1) as f64;
+    order.into_iter().enumerate().map(|(rank, id)| (id, 1.0 - rank as f64 / last)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn determinized_hands_keep_what_is_known() {
+        let mut game = UnoGame::with_seed(21);
+        let host = PlayerId::from("alice");
+        for (id, name) in [("alice", "Alice"), ("bob", "Bob"), ("carol", "Carol")] {
+            game.add_player(id, name).unwrap();
+        }
+        game.start(&host).unwrap();
+        let mut bot = Ismcts::new(20).with_seed(1);
+        for _ in 0..12 {
+            let me = game.get_curr_player().id().clone();
+            let play = bot.choose(&game, &me).unwrap();
+            assert!(game.legal_moves(&me).contains(&play), "{}", play);
+
+            let mut rng = StdRng::seed_from_u64(2);
+            let sim = determinize(&game.sandbox(&mut rng), &me, &voids(&game, &me), &mut rng);
+            assert!(sim.get_curr_player().cards() == game.get_curr_player().cards());
+            for (real, guess) in game.players().zip(sim.players()) {
+                assert_eq!(real.cards().len(), guess.cards().len());
+            }
+            assert_eq!(sim.deck_size(), game.deck_size());
+            game.make_move(&play).unwrap();
+        }
+    }
+
+    #[test]
+    fn draws_only_reveal_voids_under_must_play() {
+        for must_play in [false, true] {
+            let mut game = UnoGame::with_seed(8);
+            let host = PlayerId::from("alice");
+            game.add_player("alice", "Alice").unwrap();
+            game.add_player("bob", "Bob").unwrap();
+            game.set_rule(&host, "must_play", if must_play { "on" } else { "off" }).unwrap();
+            game.start(&host).unwrap();
+            for _ in 0..20 {
+                let me = game.get_curr_player().id().clone();
+                let moves = game.legal_moves(&me);
+                game.make_move(moves.last().unwrap()).unwrap();
+            }
+            let bob = PlayerId::from("bob");
+            assert!(game.log().entries.iter().any(|entry| entry.by == bob && entry.action == Action::Draw));
+            assert_eq!(voids(&game, &host).contains_key(&bob), must_play);
+        }
+    }
+}
@@ -1,8 +1,25 @@
 pub mod uno_game;
-mod player;
+pub mod player;
 
-mod rules;
-mod card;
+pub mod rules;
+pub mod card;
+pub mod card_input;
+pub mod clock;
+pub mod history;
+pub mod replay;
+pub mod moves;
+pub mod strategy;
+pub mod ismcts;
+pub mod bots;
+pub mod engine;
+pub mod tournament;
+pub mod notation;
+pub mod observer;
+pub mod spectator;
+pub mod rating;
+pub mod i18n;
+#[cfg(feature = "persistence")]
+pub mod persistence;
 
 mod game_manager {
     
@@ -1,13 +1,37 @@
-use std::thread::sleep;
-use uno;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::env;
+use std::fs;
+use std::process::exit;
+use std::time::Duration;
+use uno::clock::{Clock, ManualClock, SystemClock};
+use uno::player::PlayerId;
 fn main(){
+    let args: Vec<String> = env::args().collect();
+    let clock = ManualClock::new(SystemClock.now());
     let mut game = uno::uno_game::UnoGame::new();
-    game.add_player("test");
-    game.add_player("new test");
-    game.start();
+    game.set_clock(clock.clone());
+    game.add_player(1u64, "test").unwrap();
+    game.add_player(2u64, "new test").unwrap();
+    let host = PlayerId::from(1u64);
+    if let Some(pos) = args.iter().position(|arg| arg == "--rules") {
+        let Some(path) = args.get(pos + 1) else {
+            eprintln!("--rules needs a file");
+            exit(2)
+        };
+        let input = match fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("Could not read {}: {}", path, err);
+                exit(1)
+            }
+        };
+        if let Err(err) = game.import_rules(&host, input.as_str()) {
+            eprintln!("{}", err);
+            exit(1)
+        }
+    }
+    game.start(&host).unwrap();
     println!("{}",game.scoreboard());
-    sleep(Duration::new(65, 0));
+    clock.advance(Duration::new(65, 0));
     println!("{}",game.scoreboard());
     println!("TEST")
 }
\ No newline at end of file
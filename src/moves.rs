@@ -0,0 +1,192 @@
+use std::fmt::Display;
+use crate::card::{Card, COLORS};
+use crate::history::Action;
+use crate::player::PlayerId;
+use crate::rules::RuleKey;
+use crate::uno_game::UnoGame;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Play { card: String, color: String },
+    /// Several cards with the same face in one turn, under Multi-Play. The last color is the one that stays on top.
+    PlayMany { card: String, colors: Vec<String> },
+    Draw,
+}
+
+impl Move {
+    pub fn input(&self) -> String {
+        match self {
+            Move::Play { card, color } => Action::play_text(card, color),
+            Move::PlayMany { card, colors } => colors.iter().map(|color| Action::play_text(card, color)).collect::<Vec<String>>().join(" & "),
+            Move::Draw => "draw".to_string(),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Play { .. } | Move::PlayMany { .. } => write!(f, "play {}", self.input()),
+            Move::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+impl UnoGame {
+    pub fn legal_moves(&self, player_id: &PlayerId) -> Vec<Move> {
+        if !self.is_started() || self.is_over() || self.get_curr_player().id() != player_id {
+            return vec![]
+        }
+        let top = self.get_curr_card();
+        let mut moves = Vec::new();
+        for card in self.get_curr_player().cards().iter().filter(|card| card.can_play_on(top)) {
+            let colors = if card.is_wild() { COLORS.to_vec() } else { vec![card.color()] };
+            for color in colors {
+                let play = Move::Play { card: card.id().to_string(), color: color.to_string() };
+                if !moves.contains(&play) {
+                    moves.push(play);
+                }
+            }
+        }
+        if self.rules().bool(RuleKey::MultiPlay) {
+            moves.extend(self.multi_plays());
+        }
+        if moves.is_empty() || !self.rules().bool(RuleKey::MustPlay) {
+            moves.push(Move::Draw);
+        }
+        moves
+    }
+
+    pub fn make_move(&mut self, play: &Move) -> Result<String,String> {
+        match play {
+            Move::Play { card, color } => self.play(Action::play_text(card, color)),
+            Move::PlayMany { card, colors } => {
+                let cards = colors.iter().map(|color| Action::play_text(card, color)).collect::<Vec<String>>();
+                self.play_many(&cards.iter().map(|card| card.as_str()).collect::<Vec<&str>>())
+            }
+            Move::Draw => self.draw(),
+        }
+    }
+
+    /// Every distinct way to play two or more cards with the same face. Which cards of each color go
+    /// and which color ends on top change the game, the order of the cards in between doesn't.
+    fn multi_plays(&self) -> Vec<Move> {
+        let top = self.get_curr_card();
+        let hand = self.get_curr_player().cards();
+        let mut ids = hand.iter().map(|card| card.id()).collect::<Vec<&str>>();
+        ids.sort();
+        ids.dedup();
+        let mut moves = Vec::new();
+        for id in ids {
+            let same = hand.iter().filter(|card| card.id() == id).collect::<Vec<&Card>>();
+            if same.len() < 2 || !same.iter().any(|card| card.can_play_on(top)) {
+                continue
+            }
+            if same[0].is_wild() {
+                for count in 2..=same.len() {
+                    for color in COLORS {
+                        moves.push(Move::PlayMany { card: id.to_string(), colors: vec![color.to_string(); count] });
+                    }
+                }
+                continue
+            }
+            let held = COLORS.map(|color| same.iter().filter(|card| card.color() == color).count());
+            let options = held.iter().map(|count| count + 1).product::<usize>();
+            for option in 0..options {
+                let mut rest = option;
+                let picked = held.map(|count| {
+                    let picked = rest % (count + 1);
+                    rest /= count + 1;
+                    picked
+                });
+                if picked.iter().sum::<usize>() < 2 {
+                    continue
+                }
+                for last in (0..COLORS.len()).filter(|last| picked[*last] > 0) {
+                    let mut left = picked;
+                    left[last] -= 1;
+                    let Some(first) = (0..COLORS.len()).find(|first| left[*first] > 0 && same.iter().any(|card| card.color() == COLORS[*first] && card.can_play_on(top))) else {
+                        continue
+                    };
+                    left[first] -= 1;
+                    let mut colors = vec![COLORS[first].to_string()];
+                    for (color, count) in COLORS.iter().zip(left) {
+                        colors.extend(std::iter::repeat_n(color.to_string(), count));
+                    }
+                    colors.push(COLORS[last].to_string());
+                    moves.push(Move::PlayMany { card: id.to_string(), colors });
+                }
+            }
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legal_moves_are_exactly_the_playable_ones() {
+        let mut game = UnoGame::with_seed(11);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.set_rule(&host, "must_play", "on").unwrap();
+        game.start(&host).unwrap();
+        for _ in 0..40 {
+            if game.is_over() {
+                break
+            }
+            let current = game.get_curr_player().id().clone();
+            let other = game.players().nth(1).unwrap().id().clone();
+            assert!(game.legal_moves(&other).is_empty());
+            let moves = game.legal_moves(&current);
+            assert_eq!(moves.contains(&Move::Draw), moves == [Move::Draw]);
+            let hand = game.get_curr_player().cards().iter().map(|card| (card.id().to_string(), card.color().to_string())).collect::<Vec<_>>();
+            for (card, color) in hand {
+                let colors = if color.is_empty() { COLORS.iter().map(|color| color.to_string()).collect() } else { vec![color] };
+                for color in colors {
+                    let play = Move::Play { card: card.clone(), color };
+                    let mut copy = UnoGame::replay(game.log()).unwrap();
+                    assert_eq!(copy.make_move(&play).is_ok(), moves.contains(&play), "{}", play);
+                }
+            }
+            game.make_move(&moves[0]).unwrap();
+        }
+    }
+
+    #[test]
+    fn multi_play_offers_every_same_face_group() {
+        let mut offered = 0;
+        for seed in 0..6 {
+            let mut game = UnoGame::with_seed(seed);
+            let host = PlayerId::from("alice");
+            game.add_player("alice", "Alice").unwrap();
+            game.add_player("bob", "Bob").unwrap();
+            game.set_rule(&host, "multi_play", "on").unwrap();
+            game.set_rule(&host, "initial_cards", "12").unwrap();
+            game.start(&host).unwrap();
+            for _ in 0..20 {
+                if game.is_over() {
+                    break
+                }
+                let current = game.get_curr_player().id().clone();
+                let moves = game.legal_moves(&current);
+                for play in moves.iter().filter(|play| matches!(play, Move::PlayMany { .. })) {
+                    let Move::PlayMany { card, colors } = play else {
+                        unreachable!()
+                    };
+                    let words = play.to_string();
+                    assert_eq!(crate::engine::parse_move(&words.split_whitespace().collect::<Vec<&str>>()).as_ref(), Some(play));
+                    let mut copy = UnoGame::replay(game.log()).unwrap();
+                    copy.make_move(play).unwrap();
+                    assert!(copy.get_curr_card().id() == card && copy.get_curr_card().color() == colors[colors.len() - 1]);
+                    offered += 1;
+                }
+                game.make_move(moves.last().unwrap()).unwrap();
+            }
+        }
+        assert!(offered > 0);
+    }
+}
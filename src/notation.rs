@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use crate::history::{Action, GameLog, LogEntry};
+use crate::i18n::{Locale, Msg};
+use crate::card_input::{self, Aliases};
+use crate::player::PlayerId;
+use crate::rules::{Rule, RuleFormat, Rules};
+use crate::uno_game::UnoGame;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+struct Token {
+    text: String,
+    column: usize,
+    quoted: bool,
+}
+
+pub fn to_notation(log: &GameLog) -> String {
+    let mut labels: Vec<(PlayerId, String)> = Vec::new();
+    for entry in &log.entries {
+        if let Action::Join { name, .. } = &entry.action {
+            if !labels.iter().any(|(id, _)| *id == entry.by) {
+                labels.push((entry.by.clone(), name.clone()));
+            }
+        }
+    }
+    let label = |id: &PlayerId| -> String {
+        match labels.iter().position(|(player, _)| player == id) {
+            Some(idx) => format!("P{}", idx + 1),
+            None => id_text(id),
+        }
+    };
+
+    let mut out = String::new();
+    out.push_str(format!("[Seed \"{}\"]\n", log.seed).as_str());
+    let start = log.entries.first().map(|entry| entry.at).unwrap_or(0);
+    out.push_str(format!("[Start \"{}\"]\n", start).as_str());
+    for (idx, (id, name)) in labels.iter().enumerate() {
+        out.push_str(format!("[Player \"P{}\" {} {}]\n", idx + 1, id_text(id), quote(name)).as_str());
+    }
+    if let Ok(rules) = Rules::new().import(log.rules.as_str(), Locale::default()) {
+        let defaults = Rules::new();
+        for (rule, value) in rules.iter() {
+            if defaults.get(rule.key) != value {
+                out.push_str(format!("[Rule \"{}\" \"{}\"]\n", rule.id, value).as_str());
+            }
+        }
+        for (alias, _) in defaults.aliases().iter().filter(|(alias, _)| rules.aliases().get(alias).is_none()) {
+            out.push_str(format!("[Unalias {}]\n", quote(alias)).as_str());
+        }
+        for (alias, card) in rules.aliases().iter().filter(|(alias, card)| defaults.aliases().get(alias) != Some(*card)) {
+            out.push_str(format!("[Alias {} \"{}\"]\n", quote(alias), card).as_str());
+        }
+    }
+    out.push('\n');
+
+    for entry in &log.entries {
+        let by = label(&entry.by);
+        let action = match &entry.action {
+            Action::Join { seat: Some(seat), .. } => format!("JOIN @{}", seat),
+            Action::Join { seat: None, .. } => "JOIN".to_string(),
+            Action::Leave => "LEAVE".to_string(),
+            Action::Kick { player } => format!("KICK {}", label(player)),
+            Action::Start => "START".to_string(),
+            Action::Play { card, color } => card_token(card, color),
+            Action::PlayMany { card, colors } => colors.iter().map(|color| card_token(card, color)).collect::<Vec<String>>().join(" & "),
+            Action::Draw => "D1".to_string(),
+            Action::Timeout => "TIMEOUT".to_string(),
+            Action::RemoveAfk => "AFK".to_string(),
+            Action::Uno => "UNO".to_string(),
+            Action::Callout { caught } => {
+                let mut text = "CALLOUT".to_string();
+                for player in caught {
+                    text.push(' ');
+                    text.push_str(label(player).as_str());
+                }
+                text
+            }
+            Action::SetRule { rule, value } => format!("RULE {} {}", rule, quote(value)),
+            Action::ApplyPreset { preset } => format!("PRESET {}", preset),
+            Action::ImportRules { input } => format!("IMPORT {}", quote(input)),
+            Action::AddAlias { alias, card } => format!("ALIAS {} {}", quote(alias), card),
+            Action::RemoveAlias { alias } => format!("UNALIAS {}", quote(alias)),
+            Action::SetHost { player } => format!("HOST {}", label(player)),
+            Action::LockRules => "LOCK".to_string(),
+            Action::UnlockRules => "UNLOCK".to_string(),
+            Action::End => "END".to_string(),
+            Action::VoteKick { player } => format!("VOTEKICK {}", label(player)),
+            Action::VoteRule { rule, value } => format!("VOTERULE {} {}", rule, quote(value)),
+        };
+        out.push_str(format!("{}: {} {{+{}}}\n", by, action, entry.at.saturating_sub(start)).as_str());
+    }
+    out
+}
+
+pub fn parse(input: &str) -> Result<GameLog, ParseError> {
+    let mut seed: Option<u64> = None;
+    let mut start: u64 = 0;
+    let mut rules = Rules::new();
+    let mut aliases: Option<Aliases> = None;
+    let mut labels: HashMap<String, (PlayerId, String)> = HashMap::new();
+    let mut entries = Vec::new();
+
+    for (idx, raw) in input.lines().enumerate() {
+        let line = idx + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with(';') {
+            continue
+        }
+        let offset = raw.len() - raw.trim_start().len();
+        if trimmed.starts_with('[') {
+            let Some(body) = trimmed.strip_prefix('[').and_then(|body| body.strip_suffix(']')) else {
+                return Err(error(line, offset + 1, "Header is missing its closing ']'"))
+            };
+            let tokens = tokenize(body, line, offset + 2)?;
+            let Some(key) = tokens.first() else {
+                return Err(error(line, offset + 1, "Empty header"))
+            };
+            let arg = |n: usize| -> Result<&Token, ParseError> {
+                tokens.get(n).ok_or_else(|| error(line, offset + 1 + body.len(), format!("{} header needs {} value(s)", key.text, n).as_str()))
+            };
+            match key.text.as_str() {
+                "Seed" => {
+                    let value = arg(1)?;
+                    seed = Some(value.text.parse().map_err(|_| error(line, value.column, "Seed must be a whole number"))?);
+                }
+                "Start" => {
+                    let value = arg(1)?;
+                    start = value.text.parse().map_err(|_| error(line, value.column, "Start must be a timestamp in milliseconds"))?;
+                }
+                "Player" => {
+                    let (label, id, name) = (arg(1)?, arg(2)?, arg(3)?);
+                    labels.insert(label.text.clone(), (read_id(id), name.text.clone()));
+                }
+                "Rule" => {
+                    let (key, value) = (arg(1)?, arg(2)?);
+                    let rule = Rule::find(key.text.as_str()).ok_or_else(|| error(line, key.column, Msg::RuleNotFound { rule: key.text.clone() }.text(Locale::En).as_str()))?;
+                    let value = rule.parse(value.text.as_str()).map_err(|err| error(line, value.column, err.text(Locale::En).as_str()))?;
+                    rules.set(rule.key, value).map_err(|err| error(line, key.column, err.text(Locale::En).as_str()))?;
+                }
+                "Alias" => {
+                    let (alias, card) = (arg(1)?, arg(2)?);
+                    rules.aliases_mut().add(alias.text.as_str(), card.text.as_str()).map_err(|err| error(line, alias.column, err.text(Locale::En).as_str()))?;
+                }
+                "Unalias" => {
+                    let alias = arg(1)?;
+                    rules.aliases_mut().remove(alias.text.as_str()).ok_or_else(|| error(line, alias.column, format!("Unknown alias '{}'", alias.text).as_str()))?;
+                }
+                _ => {}
+            }
+            continue
+        }
+
+        let Some(colon) = trimmed.find(':') else {
+            return Err(error(line, offset + 1, "Expected a move like 'P1: R5'"))
+        };
+        let body = &trimmed[colon + 1..];
+        let by = resolve(&labels, &trimmed[..colon], line, offset + 1)?;
+        let mut tokens = tokenize(body, line, offset + colon + 2)?;
+        let mut at = start;
+        if let Some(comment) = tokens.last().filter(|token| !token.quoted && token.text.starts_with('{')) {
+            let Some(text) = comment.text.strip_prefix('{').and_then(|text| text.strip_suffix('}')) else {
+                return Err(error(line, comment.column, "Comment is missing its closing '}'"))
+            };
+            if let Some(ms) = text.trim().strip_prefix('+') {
+                at = start + ms.parse::<u64>().map_err(|_| error(line, comment.column + 1, "Time offset must be a whole number"))?;
+            }
+            tokens.pop();
+        }
+        let Some(first) = tokens.first() else {
+            return Err(error(line, offset + colon + 2, "Missing move after the player"))
+        };
+        let word = |n: usize| -> Result<&Token, ParseError> {
+            tokens.get(n).ok_or_else(|| error(line, offset + trimmed.len(), format!("{} needs more arguments", first.text).as_str()))
+        };
+        let action = match first.text.to_uppercase().as_str() {
+            "JOIN" => {
+                let name = labels.values().find(|(id, _)| *id == by).map(|(_, name)| name.clone()).unwrap_or_else(|| by.to_string());
+                let seat = match tokens.get(1) {
+                    Some(seat) => Some(seat.text.trim_start_matches('@').parse::<usize>().map_err(|_| error(line, seat.column, "Seat must be written as @N"))?),
+                    None => None,
+                };
+                Action::Join { name, seat }
+            }
+            "LEAVE" => Action::Leave,
+            "KICK" => Action::Kick { player: resolve(&labels, word(1)?.text.as_str(), line, word(1)?.column)? },
+            "START" => Action::Start,
+            "D" | "D1" | "DRAW" => Action::Draw,
+            "TIMEOUT" => Action::Timeout,
+            "AFK" => Action::RemoveAfk,
+            "UNO" => Action::Uno,
+            "CALLOUT" => {
+                let mut caught = Vec::new();
+                for token in &tokens[1..] {
+                    caught.push(resolve(&labels, token.text.as_str(), line, token.column)?);
+                }
+                Action::Callout { caught }
+            }
+            "RULE" => Action::SetRule { rule: word(1)?.text.clone(), value: word(2)?.text.clone() },
+            "PRESET" => Action::ApplyPreset { preset: word(1)?.text.clone() },
+            "IMPORT" => Action::ImportRules { input: word(1)?.text.clone() },
+            "ALIAS" => {
+                let (alias, card) = (word(1)?, word(2)?);
+                if let Err(err) = aliases.get_or_insert_with(|| rules.aliases().clone()).add(alias.text.as_str(), card.text.as_str()) {
+                    let column = if matches!(err, Msg::AliasUnknownCard { .. }) { card.column } else { alias.column };
+                    return Err(error(line, column, err.text(Locale::En).as_str()))
+                }
+                Action::AddAlias { alias: alias.text.clone(), card: card.text.clone() }
+            }
+            "UNALIAS" => {
+                let alias = word(1)?;
+                aliases.get_or_insert_with(|| rules.aliases().clone()).remove(alias.text.as_str()).ok_or_else(|| error(line, alias.column, format!("Unknown alias '{}'", alias.text).as_str()))?;
+                Action::RemoveAlias { alias: alias.text.clone() }
+            }
+            "HOST" => Action::SetHost { player: resolve(&labels, word(1)?.text.as_str(), line, word(1)?.column)? },
+            "LOCK" => Action::LockRules,
+            "UNLOCK" => Action::UnlockRules,
+            "END" => Action::End,
+            "VOTEKICK" => Action::VoteKick { player: resolve(&labels, word(1)?.text.as_str(), line, word(1)?.column)? },
+            "VOTERULE" => Action::VoteRule { rule: word(1)?.text.clone(), value: word(2)?.text.clone() },
+            _ => {
+                let words = tokens.iter().map(|token| token.text.as_str()).collect::<Vec<&str>>();
+                let unknown = || error(line, first.column, format!("Unknown card or move '{}'", words.join(" ")).as_str());
+                let mut plays = Vec::new();
+                for group in words.split(|word| *word == "&") {
+                    plays.push(parse_card(group, aliases.as_ref().unwrap_or(rules.aliases())).ok_or_else(unknown)?);
+                }
+                match plays.as_slice() {
+                    [(card, color)] => Action::Play { card: card.clone(), color: color.clone() },
+                    _ if plays.iter().all(|(card, _)| *card == plays[0].0) => Action::PlayMany {
+                        card: plays[0].0.clone(),
+                        colors: plays.iter().map(|(_, color)| color.clone()).collect(),
+                    },
+                    _ => return Err(unknown()),
+                }
+            }
+        };
+        entries.push(LogEntry { at, by, action });
+    }
+
+    let seed = seed.ok_or_else(|| error(1, 1, "Missing [Seed \"...\"] header"))?;
+    Ok(GameLog {
+        seed,
+        rules: rules.export(RuleFormat::Toml),
+        entries,
+    })
+}
+
+pub fn parse_card(words: &[&str], aliases: &Aliases) -> Option<(String, String)> {
+    let spec = card_input::parse(words.join(" ").as_str(), aliases).ok()?;
+    Some((spec.id, spec.color?))
+}
+
+fn card_token(card: &str, color: &str) -> String {
+    match card {
+        "WILD" => format!("W {}", color),
+        "WILD+4" => format!("W+4 {}", color),
+        _ => format!("{}{}", color, card),
+    }
+}
+
+fn resolve(labels: &HashMap<String, (PlayerId, String)>, label: &str, line: usize, column: usize) -> Result<PlayerId, ParseError> {
+    let label = label.trim();
+    if let Some((id, _)) = labels.get(label) {
+        return Ok(id.clone())
+    }
+    if label.starts_with('"') || label.parse::<u64>().is_ok() {
+        let tokens = tokenize(label, line, column)?;
+        if let Some(token) = tokens.first() {
+            return Ok(read_id(token))
+        }
+    }
+    Err(error(line, column, format!("Unknown player '{}'", label).as_str()))
+}
+
+fn tokenize(input: &str, line: usize, column: usize) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let chars = input.char_indices().collect::<Vec<(usize, char)>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue
+        }
+        if c == '"' {
+            let mut j = i + 1;
+            let mut escaped = false;
+            while j < chars.len() && (escaped || chars[j].1 != '"') {
+                escaped = !escaped && chars[j].1 == '\\';
+                j += 1;
+            }
+            if j >= chars.len() {
+                return Err(error(line, column + pos, "Unterminated string"))
+            }
+            let end = chars[j].0;
+            let text: String = serde_json::from_str(&input[pos..=end]).map_err(|_| error(line, column + pos, "Invalid escape in string"))?;
+            tokens.push(Token { text, column: column + pos, quoted: true });
+            i = j + 1;
+        }
+        else {
+            let mut j = i;
+            while j < chars.len() && !chars[j].1.is_whitespace() {
+                j += 1;
+            }
+            let end = if j < chars.len() { chars[j].0 } else { input.len() };
+            tokens.push(Token { text: input[pos..end].to_string(), column: column + pos, quoted: false });
+            i = j;
+        }
+    }
+    Ok(tokens)
+}
+
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+/// Numeric ids are written bare and string ids quoted, so `1` and `"1"` read back as different players.
+fn id_text(id: &PlayerId) -> String {
+    serde_json::to_string(id).unwrap()
+}
+
+fn read_id(token: &Token) -> PlayerId {
+    match token.text.parse::<u64>() {
+        Ok(id) if !token.quoted => PlayerId::from(id),
+        _ => PlayerId::from(token.text.as_str()),
+    }
+}
+
+fn error(line: usize, column: usize, message: &str) -> ParseError {
+    ParseError {
+        line,
+        column,
+        message: message.to_string(),
+    }
+}
+
+impl UnoGame {
+    pub fn to_notation(&self) -> String {
+        to_notation(self.log())
+    }
+
+    pub fn from_notation(input: &str) -> Result<UnoGame, String> {
+        let log = parse(input).map_err(|err| err.to_string())?;
+        UnoGame::replay(&log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_game() {
+        let mut game = UnoGame::with_seed(3);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob \"the builder\"").unwrap();
+        game.add_player(7u64, "Seven").unwrap();
+        game.add_player("7", "Other seven").unwrap();
+        game.set_rule(&host, "initial_cards", "3").unwrap();
+        game.start(&host).unwrap();
+        for _ in 0..10 {
+            game.draw().unwrap();
+        }
+        let text = game.to_notation();
+        let replayed = UnoGame::from_notation(text.as_str()).unwrap();
+        assert!(text.contains("[Player \"P3\" 7 \"Seven\"]") && text.contains("[Player \"P4\" \"7\" \"Other seven\"]"));
+        assert_eq!(replayed.table(), game.table());
+        assert_eq!(replayed.log(), game.log());
+        assert_eq!(replayed.to_notation(), text);
+    }
+
+    #[test]
+    fn reads_card_aliases() {
+        let aliases = Aliases::default();
+        assert_eq!(parse_card(&["R5"], &aliases), Some(("5".to_string(), "R".to_string())));
+        assert_eq!(parse_card(&["5", "red"], &aliases), Some(("5".to_string(), "R".to_string())));
+        assert_eq!(parse_card(&["W+4", "B"], &aliases), Some(("WILD+4".to_string(), "B".to_string())));
+        assert_eq!(parse_card(&["g", "rev"], &aliases), Some(("REVERSE".to_string(), "G".to_string())));
+        assert_eq!(parse_card(&["W"], &aliases), None);
+    }
+
+    #[test]
+    fn reads_moves_with_custom_aliases() {
+        let log = parse("[Seed \"1\"]\n[Player \"P1\" \"a\" \"A\"]\n[Alias \"boom\" \"WILD\"]\nP1: JOIN {+0}\nP1: boom G {+0}\n").unwrap();
+        assert_eq!(log.entries[1].action, Action::Play { card: "WILD".to_string(), color: "G".to_string() });
+        let text = to_notation(&log);
+        assert!(text.contains("[Alias \"boom\" \"WILD\"]") && text.ends_with("P1: W G {+0}\n"));
+        assert_eq!(parse(text.as_str()).unwrap(), log);
+
+        let mut game = UnoGame::with_seed(5);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.add_alias(&host, "nuke", "WILD+4").unwrap();
+        let text = game.to_notation();
+        let log = parse(format!("{}P1: nuke R {{+0}}\n", text).as_str()).unwrap();
+        assert_eq!(log.entries.last().unwrap().action, Action::Play { card: "WILD+4".to_string(), color: "R".to_string() });
+        assert!(parse(format!("{}P1: UNALIAS nuke\nP1: nuke R\n", text).as_str()).is_err());
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let err = parse("[Seed \"1\"]\n[Player \"P1\" \"a\" \"A\"]\nP1: JOIN\nP1: Q9\n").unwrap_err();
+        assert_eq!((err.line, err.column), (4, 5));
+        let err = parse("[Seed \"1\"]\nP7: START\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        let moves = "[Seed \"1\"]\n[Player \"P1\" \"a\" \"A\"]\nP1: JOIN\n";
+        let err = parse(format!("{}P1: ALIAS boom Q9\nP1: boom G\n", moves).as_str()).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 16));
+        let err = parse(format!("{}P1: ALIAS red WILD\n", moves).as_str()).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 11));
+        let err = parse(format!("{}P1: UNALIAS boom\n", moves).as_str()).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 13));
+    }
+}
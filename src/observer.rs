@@ -0,0 +1,119 @@
+use crate::card::{color_text, face_text, Card};
+use crate::i18n::Msg;
+use crate::player::PlayerId;
+use crate::uno_game::UnoGame;
+
+pub trait Observer {
+    fn on_turn_start(&mut self, _game: &UnoGame, _player: &PlayerId) {}
+    fn on_card_played(&mut self, _game: &UnoGame, _player: &PlayerId, _card: &Card) {}
+    fn on_draw(&mut self, _game: &UnoGame, _player: &PlayerId, _count: i32, _penalty: bool) {}
+    fn on_uno(&mut self, _game: &UnoGame, _player: &PlayerId) {}
+    fn on_callout(&mut self, _game: &UnoGame, _by: &PlayerId, _caught: &[PlayerId]) {}
+    fn on_player_finished(&mut self, _game: &UnoGame, _player: &PlayerId, _rank: usize) {}
+    fn on_game_end(&mut self, _game: &UnoGame) {}
+}
+
+#[derive(Clone)]
+pub(crate) enum GameEvent {
+    TurnStart(PlayerId),
+    CardPlayed(PlayerId, Card),
+    Draw(PlayerId, i32, bool),
+    Uno(PlayerId),
+    Callout(PlayerId, Vec<PlayerId>),
+    PlayerFinished(PlayerId, usize),
+    GameEnd,
+}
+
+impl GameEvent {
+    pub(crate) fn notify(&self, observer: &mut dyn Observer, game: &UnoGame) {
+        match self {
+            GameEvent::TurnStart(player) => observer.on_turn_start(game, player),
+            GameEvent::CardPlayed(player, card) => observer.on_card_played(game, player, card),
+            GameEvent::Draw(player, count, penalty) => observer.on_draw(game, player, *count, *penalty),
+            GameEvent::Uno(player) => observer.on_uno(game, player),
+            GameEvent::Callout(by, caught) => observer.on_callout(game, by, caught),
+            GameEvent::PlayerFinished(player, rank) => observer.on_player_finished(game, player, *rank),
+            GameEvent::GameEnd => observer.on_game_end(game),
+        }
+    }
+
+    pub(crate) fn describe(&self, game: &UnoGame) -> String {
+        let locale = game.locale();
+        let msg = match self {
+            GameEvent::TurnStart(player) => Msg::TurnStart { name: game.player_name(player) },
+            GameEvent::CardPlayed(player, card) if card.is_wild() => Msg::PlayedWild { name: game.player_name(player), card: face_text(card.id(), locale), color: color_text(card.color(), locale) },
+            GameEvent::CardPlayed(player, card) => Msg::Played { name: game.player_name(player), card: card.text(locale) },
+            GameEvent::Draw(player, count, false) => Msg::DrewCards { name: game.player_name(player), count: *count },
+            GameEvent::Draw(player, count, true) => Msg::PickedUp { name: game.player_name(player), count: *count },
+            GameEvent::Uno(player) => Msg::SaidUno { name: game.player_name(player) },
+            GameEvent::Callout(by, caught) if caught.is_empty() => Msg::CalledOutNobody { name: game.player_name(by) },
+            GameEvent::Callout(by, caught) => {
+                let names = caught.iter().map(|player| game.player_name(player)).collect::<Vec<String>>().join(", ");
+                Msg::CalledOut { name: game.player_name(by), names }
+            }
+            GameEvent::PlayerFinished(player, rank) => Msg::PlayerFinished { name: game.player_name(player), rank: *rank },
+            GameEvent::GameEnd => Msg::GameOver,
+        };
+        msg.text(locale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use super::*;
+
+    struct Recorder {
+        name: &'static str,
+        seen: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Observer for Recorder {
+        fn on_turn_start(&mut self, _game: &UnoGame, player: &PlayerId) {
+            self.seen.borrow_mut().push(format!("{} turn {}", self.name, player));
+        }
+
+        fn on_draw(&mut self, _game: &UnoGame, player: &PlayerId, count: i32, penalty: bool) {
+            self.seen.borrow_mut().push(format!("{} draw {} {} {}", self.name, player, count, penalty));
+        }
+
+        fn on_player_finished(&mut self, _game: &UnoGame, player: &PlayerId, rank: usize) {
+            self.seen.borrow_mut().push(format!("{} finished {} {}", self.name, player, rank));
+        }
+
+        fn on_game_end(&mut self, game: &UnoGame) {
+            assert!(game.is_over());
+            self.seen.borrow_mut().push(format!("{} end", self.name));
+        }
+    }
+
+    #[test]
+    fn observers_are_called_in_order() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut game = UnoGame::with_seed(5);
+        let host = PlayerId::from("alice");
+        game.add_observer(Box::new(Recorder { name: "first", seen: seen.clone() }));
+        game.add_observer(Box::new(Recorder { name: "second", seen: seen.clone() }));
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.start(&host).unwrap();
+        let first = game.get_curr_player().id().clone();
+        let second = game.players().nth(1).unwrap().id().clone();
+        game.draw().unwrap();
+        game.end(&host).unwrap();
+
+        let expected = [
+            format!("turn {}", first),
+            format!("draw {} 1 false", first),
+            format!("turn {}", second),
+            format!("finished {} 1", second),
+            format!("finished {} 2", first),
+            "end".to_string(),
+        ];
+        let expected = expected.iter()
+            .flat_map(|event| [format!("first {}", event), format!("second {}", event)])
+            .collect::<Vec<String>>();
+        assert_eq!(*seen.borrow(), expected);
+    }
+}
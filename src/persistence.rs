@@ -0,0 +1,273 @@
+use std::path::Path;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use crate::history::Action;
+use crate::player::{PlayerId, PlayerStats};
+use crate::rating;
+use crate::rules::RuleFormat;
+use crate::uno_game::UnoGame;
+
+// Player ids have no declared type so numeric ids stay integers and 1 and '1' remain different players.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS players (
+    id PRIMARY KEY,
+    username TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    seed INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER NOT NULL,
+    drawn INTEGER NOT NULL,
+    rules TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    player_id NOT NULL REFERENCES players(id),
+    rank INTEGER NOT NULL,
+    dropped INTEGER NOT NULL,
+    cards_played INTEGER NOT NULL,
+    cards_drawn INTEGER NOT NULL,
+    penalty_cards INTEGER NOT NULL,
+    action_cards INTEGER NOT NULL,
+    wilds_played INTEGER NOT NULL,
+    uno_calls INTEGER NOT NULL,
+    callouts INTEGER NOT NULL,
+    false_callouts INTEGER NOT NULL,
+    turns INTEGER NOT NULL,
+    longest_turn INTEGER NOT NULL,
+    finished_after INTEGER,
+    PRIMARY KEY (game_id, player_id)
+);
+";
+
+const CAREER: &str = "
+SELECT players.id, players.username, COUNT(*), SUM(results.rank = 1), AVG(results.rank),
+    SUM(cards_played), SUM(cards_drawn), SUM(penalty_cards), SUM(action_cards), SUM(wilds_played),
+    SUM(uno_calls), SUM(callouts), SUM(false_callouts), SUM(turns), MAX(longest_turn)
+FROM results JOIN players ON players.id = results.player_id
+";
+
+pub struct Database {
+    conn: Connection,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Career {
+    pub id: PlayerId,
+    pub username: String,
+    pub games: i64,
+    pub wins: i64,
+    pub average_rank: f64,
+    pub stats: PlayerStats,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeadToHead {
+    pub games: i64,
+    pub wins: i64,
+    pub losses: i64,
+}
+
+impl Career {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins as f64 / self.games as f64 }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Career> {
+        Ok(Career {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            games: row.get(2)?,
+            wins: row.get(3)?,
+            average_rank: row.get(4)?,
+            stats: PlayerStats {
+                cards_played: row.get(5)?,
+                cards_drawn: row.get(6)?,
+                penalty_cards: row.get(7)?,
+                action_cards: row.get(8)?,
+                wilds_played: row.get(9)?,
+                uno_calls: row.get(10)?,
+                callouts: row.get(11)?,
+                false_callouts: row.get(12)?,
+                turns: row.get(13)?,
+                longest_turn: row.get::<_, i64>(14)? as u64,
+                finished_after: None,
+            },
+        })
+    }
+}
+
+impl ToSql for PlayerId {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match self {
+            PlayerId::Num(id) => Ok(ToSqlOutput::from(*id as i64)),
+            PlayerId::Name(id) => Ok(ToSqlOutput::from(id.as_str())),
+        }
+    }
+}
+
+impl FromSql for PlayerId {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(id) => Ok(PlayerId::Num(id as u64)),
+            ValueRef::Text(_) => value.as_str().map(PlayerId::from),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+fn db_error(err: rusqlite::Error) -> String {
+    format!("Database error: {}", err)
+}
+
+impl Database {
+    pub fn open(path: impl AsRef<Path>) -> Result<Database, String> {
+        Database::init(Connection::open(path).map_err(db_error)?)
+    }
+
+    pub fn open_in_memory() -> Result<Database, String> {
+        Database::init(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Database, String> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Database { conn })
+    }
+
+    pub fn record_game(&mut self, game: &UnoGame) -> Result<i64, String> {
+        if !game.is_over() {
+            return Err("Only finished games can be recorded".to_string())
+        }
+        let log = game.log();
+        let started_at = log.entries.iter().find(|entry| entry.action == Action::Start).map(|entry| entry.at).unwrap_or(0);
+        let ended_at = log.entries.last().map(|entry| entry.at).unwrap_or(started_at);
+        let tx = self.conn.transaction().map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO games (seed, started_at, ended_at, drawn, rules) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![log.seed as i64, started_at as i64, ended_at as i64, game.cards_drawn(), game.export_rules(RuleFormat::Toml)],
+        ).map_err(db_error)?;
+        let game_id = tx.last_insert_rowid();
+        let standings = game.standings().iter().map(|player| (player, false));
+        let dropped = game.dropped().iter().map(|player| (player, true));
+        for ((player, dropped), (_, rank)) in standings.chain(dropped).zip(rating::game_ranks(game)) {
+            tx.execute(
+                "INSERT INTO players (id, username) VALUES (?1, ?2) ON CONFLICT(id) DO UPDATE SET username = excluded.username",
+                params![player.id(), player.username()],
+            ).map_err(db_error)?;
+            let stats = player.stats();
+            tx.execute(
+                "INSERT INTO results VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    game_id, player.id(), rank, dropped,
+                    stats.cards_played, stats.cards_drawn, stats.penalty_cards, stats.action_cards, stats.wilds_played,
+                    stats.uno_calls, stats.callouts, stats.false_callouts, stats.turns, stats.longest_turn as i64,
+                    stats.finished_after.map(|after| after as i64),
+                ],
+            ).map_err(db_error)?;
+        }
+        tx.commit().map_err(db_error)?;
+        Ok(game_id)
+    }
+
+    pub fn games_recorded(&self) -> Result<i64, String> {
+        self.conn.query_row("SELECT COUNT(*) FROM games", [], |row| row.get(0)).map_err(db_error)
+    }
+
+    pub fn career(&self, id: &PlayerId) -> Result<Option<Career>, String> {
+        let query = format!("{} WHERE players.id = ?1 GROUP BY players.id", CAREER);
+        self.conn.query_row(query.as_str(), [id], Career::from_row).optional().map_err(db_error)
+    }
+
+    pub fn top_players(&self, limit: usize) -> Result<Vec<Career>, String> {
+        let query = format!("{} GROUP BY players.id ORDER BY SUM(results.rank = 1) DESC, AVG(results.rank) ASC, players.id LIMIT ?1", CAREER);
+        let mut stmt = self.conn.prepare(query.as_str()).map_err(db_error)?;
+        let rows = stmt.query_map([limit as i64], Career::from_row).map_err(db_error)?;
+        rows.collect::<rusqlite::Result<Vec<Career>>>().map_err(db_error)
+    }
+
+    pub fn win_rate(&self, id: &PlayerId) -> Result<Option<f64>, String> {
+        Ok(self.career(id)?.map(|career| career.win_rate()))
+    }
+
+    pub fn average_rank(&self, id: &PlayerId) -> Result<Option<f64>, String> {
+        Ok(self.career(id)?.map(|career| career.average_rank))
+    }
+
+    pub fn head_to_head(&self, player: &PlayerId, opponent: &PlayerId) -> Result<HeadToHead, String> {
+        self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(ours.rank < theirs.rank), 0), COALESCE(SUM(ours.rank > theirs.rank), 0)
+            FROM results AS ours JOIN results AS theirs ON ours.game_id = theirs.game_id
+            WHERE ours.player_id = ?1 AND theirs.player_id = ?2",
+            [player, opponent],
+            |row| Ok(HeadToHead { games: row.get(0)?, wins: row.get(1)?, losses: row.get(2)? }),
+        ).map_err(db_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_game(seed: u64, players: &[&str]) -> UnoGame {
+        let mut game = UnoGame::with_seed(seed);
+        let host = PlayerId::from(players[0]);
+        for id in players {
+            game.add_player(*id, id.to_uppercase().as_str()).unwrap();
+        }
+        game.start(&host).unwrap();
+        for _ in 0..3 {
+            game.draw().unwrap();
+        }
+        game.end(&host).unwrap();
+        game
+    }
+
+    #[test]
+    fn records_careers_and_head_to_head() {
+        let mut db = Database::open_in_memory().unwrap();
+        let first = finished_game(1, &["ann", "ben", "cat"]);
+        let second = finished_game(2, &["ann", "ben"]);
+        db.record_game(&first).unwrap();
+        db.record_game(&second).unwrap();
+        assert_eq!(db.games_recorded().unwrap(), 2);
+
+        let ann = PlayerId::from("ann");
+        let ben = PlayerId::from("ben");
+        let rank_of = |game: &UnoGame, id: &PlayerId| game.ranks().into_iter().find(|(player, _)| player == id).unwrap().1 as i64;
+        let ann_ranks = [rank_of(&first, &ann), rank_of(&second, &ann)];
+        let ann_wins = ann_ranks.iter().filter(|rank| **rank == 1).count() as i64;
+
+        let career = db.career(&ann).unwrap().unwrap();
+        assert_eq!(career.username, "ANN");
+        assert_eq!(career.games, 2);
+        assert_eq!(career.wins, ann_wins);
+        assert_eq!(db.average_rank(&ann).unwrap(), Some(ann_ranks.iter().sum::<i64>() as f64 / 2.0));
+        assert_eq!(career.stats.turns, first.stats(&ann).unwrap().turns + second.stats(&ann).unwrap().turns);
+        assert_eq!(db.win_rate(&PlayerId::from("nobody")).unwrap(), None);
+
+        let versus = db.head_to_head(&ann, &ben).unwrap();
+        assert_eq!(versus.games, 2);
+        assert_eq!(versus, HeadToHead { games: 2, wins: versus.wins, losses: 2 - versus.wins });
+        assert_eq!(db.head_to_head(&ben, &ann).unwrap().wins, versus.losses);
+
+        let top = db.top_players(10).unwrap();
+        assert_eq!(top.len(), 3);
+        assert!(top.windows(2).all(|pair| pair[0].wins >= pair[1].wins));
+        assert!(db.record_game(&UnoGame::with_seed(3)).is_err());
+    }
+
+    #[test]
+    fn keeps_numeric_and_string_ids_apart() {
+        let mut db = Database::open_in_memory().unwrap();
+        let mut game = UnoGame::with_seed(4);
+        let (num, name) = (PlayerId::from(u64::MAX), PlayerId::from(u64::MAX.to_string()));
+        game.add_player(num.clone(), "Number").unwrap();
+        game.add_player(name.clone(), "Name").unwrap();
+        game.start(&num).unwrap();
+        game.end(&num).unwrap();
+        db.record_game(&game).unwrap();
+        assert_eq!(db.career(&num).unwrap().map(|career| (career.id, career.username)), Some((num, "Number".to_string())));
+        assert_eq!(db.career(&name).unwrap().map(|career| (career.id, career.username)), Some((name, "Name".to_string())));
+    }
+}
@@ -1,26 +1,93 @@
-use std::collections::HashMap;
-use crate::card::Card;
-use crate::uno_game::UnoGame;
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::card::{face_text, Card};
+use crate::card_input::{self, Aliases, CardSpec};
+use crate::i18n::{Locale, Msg};
+
+/// A player key. Numeric ids such as Discord snowflakes and string ids such as usernames are
+/// kept apart, so `PlayerId::from(1u64)` and `PlayerId::from("1")` are different players.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PlayerId {
+    Num(u64),
+    Name(String),
+}
+
+impl From<u64> for PlayerId {
+    fn from(id: u64) -> Self {
+        PlayerId::Num(id)
+    }
+}
+
+impl From<&str> for PlayerId {
+    fn from(id: &str) -> Self {
+        PlayerId::Name(id.to_string())
+    }
+}
+
+impl From<String> for PlayerId {
+    fn from(id: String) -> Self {
+        PlayerId::Name(id)
+    }
+}
+
+impl From<&PlayerId> for PlayerId {
+    fn from(id: &PlayerId) -> Self {
+        id.clone()
+    }
+}
+
+impl Display for PlayerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayerId::Num(id) => write!(f, "{}", id),
+            PlayerId::Name(id) => write!(f, "{}", id),
+        }
+    }
+}
 
 pub struct Player {
-    pub(crate) id: i32,
+    pub(crate) id: PlayerId,
     pub(crate) username: String,
     pub(crate) hand: Vec<Card>,
     pub(crate) called: bool,
     pub(crate) finished: bool,
-    pub(crate) cards_played: i32,
+    pub(crate) stats: PlayerStats,
     pub(crate) messages : Vec<String>,
 }
 
-struct PlayerOutput {
-    id: i32,
-    cards_played: i32,
-    name: String,
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub cards_played: i32,
+    pub cards_drawn: i32,
+    pub penalty_cards: i32,
+    pub action_cards: i32,
+    pub wilds_played: i32,
+    pub uno_calls: i32,
+    pub callouts: i32,
+    pub false_callouts: i32,
+    pub turns: i32,
+    pub longest_turn: u64,
+    pub finished_after: Option<u64>,
 }
 
 impl Player {
-    fn cards_changed(&mut self) {
-        self.sort_hand();
+    pub(crate) fn new(id: PlayerId, username: &str) -> Self {
+        Player {
+            id,
+            username: username.to_string(),
+            hand: vec![],
+            called: false,
+            finished: false,
+            stats: PlayerStats::default(),
+            messages: vec![],
+        }
+    }
+    pub fn id(&self) -> &PlayerId {
+        &self.id
+    }
+    pub fn username(&self) -> &str {
+        &self.username
     }
     pub fn sort_hand(&mut self) {
         self.hand.sort()
@@ -34,67 +101,47 @@ impl Player {
             _ => ""
         }
     }
-    fn format_output(&self) -> PlayerOutput {
-        PlayerOutput {
-            id: self.id,
-            cards_played: self.cards_played,
-            name: self.username.clone(),
-        }
+    pub fn stats(&self) -> &PlayerStats {
+        &self.stats
     }
-    pub fn get_card(&self, mut words: &Vec<&str>) -> Option<i32> {
-        
-        let mut color: String = String::new();
-        let mut id: String = String::new();
-        if words.len() == 1 {
-            let str_color: String = words[0].chars().next().unwrap().to_string();
-            let parsed = self.parse_color(str_color.clone());
-            if  parsed == "" {
-                id = words[0].to_string();
-            }
-            else {
-                color = String::from(parsed);
-                id = str_color;
-            };
-        } else {
-            color = words[0].parse().unwrap();
-            id = words[1].parse().unwrap();
-        };
-        if id.is_empty() {
-            return None
-        };
-        let wild: [&str; 2] = ["WILD","WILD+4"];
-        let aliases = ["W","W+4","REV","R","S","NOU","FUCKU"];
-        let wild_aliases = HashMap::from([
-            ("W".to_string(), "WILD"),
-            ("W+4".to_string(), "WILD+4"),
-            ("REV".to_string(), "REVERSE"),
-            ("R".to_string(), "REVERSE"),
-            ("NOU".to_string(), "REVERSE"),
-            ("S".to_string(), "SKIP"),
-            ("FUCKU".to_string(), "SKIP"),
-        ]);
-        let new_color = self.parse_color(color.clone());
-        if new_color.is_empty() {
-            if color.is_empty() && (wild.contains(&&*id.to_string().to_uppercase().to_string()) || aliases.contains(&&*id.to_uppercase())) {
-                return None
+    pub fn get_card(&self, words: &[&str], aliases: &Aliases) -> Option<i32> {
+        let spec = card_input::parse(words.join(" ").as_str(), aliases).ok()?;
+        self.find_card(&spec, Locale::default()).ok()
+    }
+
+    pub(crate) fn find_card(&self, spec: &CardSpec, locale: Locale) -> Result<i32, String> {
+        if spec.is_wild() || spec.color.is_some() {
+            let found = self.hand.iter().find(|card| card.id == spec.id && (spec.is_wild() || spec.color.as_deref() == Some(card.color.as_str())));
+            if let Some(card) = found {
+                return Ok(card.num)
             }
-            else {
-                (id, color) = (color,id);
-                if self.parse_color(color.clone()).is_empty() {
-                    return None
-                };
+        }
+        else {
+            let matching = self.hand.iter().filter(|card| card.id == spec.id).collect::<Vec<&Card>>();
+            match matching.as_slice() {
+                [] => {}
+                [card] => return Ok(card.num),
+                _ => {
+                    let options = matching.iter().map(|card| card.text(locale)).collect::<Vec<String>>().join(", ");
+                    return Err(Msg::WhichColor { card: face_text(&spec.id, locale), options }.text(locale))
+                }
             }
         }
-        if aliases.contains(&&*id.to_uppercase()) {
-            id = wild_aliases[&id.to_string().to_uppercase()].to_string()
+        let mut out = Msg::CardNotFound { card: spec.text(locale), name: self.username.clone() }.text(locale);
+        let similar = self.hand.iter()
+            .filter(|card| if spec.is_wild() { card.wild } else { !card.wild && (card.id == spec.id || spec.color.as_deref() == Some(card.color.as_str())) })
+            .collect::<Vec<&Card>>();
+        let gap = |card: &&&Card| match (card.id.parse::<i32>(), spec.id.parse::<i32>()) {
+            _ if card.id == spec.id => (spec.color.is_some(), 0),
+            (Ok(a), Ok(b)) => (false, (a - b).abs()),
+            _ => (false, 10),
         };
-        return if ["WILD", "WILD+4"].contains(&&*id.to_uppercase().to_string()) {
-            let found_card = self.hand.iter().find(|&card: &&Card| card.id.eq_ignore_ascii_case(id.as_str()));
-            Some(found_card.unwrap().num)
-        } else {
-            let found_card = self.hand.iter().find(|&card: &&Card| card.id.eq_ignore_ascii_case(id.as_str()) && card.color.eq_ignore_ascii_case(color.as_str()));
-            Some(found_card.unwrap().num)
+        if let Some(guess) = similar.iter().min_by_key(gap) {
+            let similar = similar.iter().map(|card| card.text(locale)).collect::<Vec<String>>().join(", ");
+            out.push_str(". ");
+            out.push_str(Msg::DidYouMean { card: guess.text(locale), similar }.text(locale).as_str());
         }
+        Err(out)
     }
 
     pub fn send_message(&mut self, message: String) {
@@ -102,16 +149,29 @@ impl Player {
     }
 
     pub fn get_hand(&mut self) -> String {
+        self.hand_message(Locale::default())
+    }
+
+    pub(crate) fn hand_message(&mut self, locale: Locale) -> String {
         self.sort_hand();
-        format!(
-            "Here is your hand:\n\n{}\n\nYou currently have {} card(s).",
-            self.hand
-                .iter()
-                .map(|card| format!("**{}**", card))
-                .collect::<Vec<String>>()
-                .join(" | "),
-            self.hand.len()
-        )
+        let cards = self.hand
+            .iter()
+            .map(|card| format!("**{}**", card.text(locale)))
+            .collect::<Vec<String>>()
+            .join(" | ");
+        Msg::Hand { cards, count: self.hand.len() }.text(locale)
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.hand
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn has_called_uno(&self) -> bool {
+        self.called
     }
 
     pub fn hand(&mut self) -> &Vec<Card> {
@@ -127,15 +187,90 @@ impl Player {
 
 impl Clone for Player {
     fn clone(&self) -> Self {
-        let player = Player {
-            id: self.id,
+        Player {
+            id: self.id.clone(),
             username: self.username.clone(),
             hand: Vec::clone(&self.hand),
             called: self.called,
             finished: self.finished,
-            cards_played: self.cards_played,
+            stats: self.stats.clone(),
             messages: Vec::clone(&self.messages),
-        };
-        return player
+        }
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+    else {
+        format!("{}s", secs)
+    }
+}
+
+impl PlayerStats {
+    pub fn summary(&self, locale: Locale) -> String {
+        let mut out = Msg::StatsLine {
+            played: self.cards_played,
+            action: self.action_cards,
+            wild: self.wilds_played,
+            drawn: self.cards_drawn,
+            penalty: self.penalty_cards,
+            uno: self.uno_calls,
+            callouts: self.callouts,
+            false_callouts: self.false_callouts,
+            turns: self.turns,
+            longest: format_duration(self.longest_turn),
+        }.text(locale);
+        if let Some(finished) = self.finished_after {
+            out.push_str(Msg::FinishedAfter { time: format_duration(finished) }.text(locale).as_str());
+        }
+        out
+    }
+}
+
+impl Display for PlayerStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.summary(Locale::En))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::Action;
+    use crate::uno_game::UnoGame;
+    use super::*;
+
+    #[test]
+    fn stats_add_up() {
+        let mut game = UnoGame::with_seed(9);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.add_player("carol", "Carol").unwrap();
+        game.set_rule(&host, "initial_cards", "4").unwrap();
+        game.start(&host).unwrap();
+        for turn in 0..5000 {
+            if game.is_over() {
+                break
+            }
+            let hand = game.get_curr_player().cards().to_vec();
+            let played = hand.iter().any(|card| game.play(if card.is_wild() { format!("{} R", card.id()) } else { format!("{} {}", card.color(), card.id()) }).is_ok());
+            if !played {
+                game.draw().unwrap();
+            }
+            if turn % 10 == 0 {
+                let _ = game.callout(&host);
+            }
+        }
+        let ids = ["alice", "bob", "carol"].map(PlayerId::from);
+        let stats = ids.iter().map(|id| game.stats(id).unwrap()).collect::<Vec<&PlayerStats>>();
+        let drawn = stats.iter().map(|stats| stats.cards_drawn + stats.penalty_cards).sum::<i32>();
+        assert_eq!(game.cards_drawn(), drawn + 12);
+        assert_eq!(stats.iter().map(|stats| stats.cards_played).sum::<i32>(), game.log().entries.iter().filter(|entry| matches!(entry.action, Action::Play { .. })).count() as i32);
+        assert_eq!(stats.iter().map(|stats| stats.turns).sum::<i32>() as usize, game.log().turns());
+        assert!(stats.iter().all(|stats| stats.finished_after.is_some()));
+        assert!(game.scoreboard().contains(stats[0].to_string().as_str()));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::player::PlayerId;
+use crate::uno_game::UnoGame;
+
+const MU: f64 = 25.0;
+const SIGMA: f64 = MU / 3.0;
+const BETA: f64 = SIGMA / 2.0;
+const KAPPA: f64 = 0.0001;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub mu: f64,
+    pub sigma: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating::new()
+    }
+}
+
+impl Rating {
+    pub fn new() -> Self {
+        Rating {
+            mu: MU,
+            sigma: SIGMA,
+        }
+    }
+
+    pub fn conservative(&self) -> f64 {
+        self.mu - 3.0 * self.sigma
+    }
+}
+
+pub fn rate(ratings: &[Rating], ranks: &[usize]) -> Vec<Rating> {
+    assert_eq!(ratings.len(), ranks.len(), "Every rating needs a rank");
+    ratings.iter().zip(ranks).enumerate().map(|(i, (rating, rank))| {
+        let variance = rating.sigma * rating.sigma;
+        let mut omega = 0.0;
+        let mut delta = 0.0;
+        for (q, (other, other_rank)) in ratings.iter().zip(ranks).enumerate() {
+            if q == i {
+                continue
+            }
+            let c = (variance + other.sigma * other.sigma + 2.0 * BETA * BETA).sqrt();
+            let p = 1.0 / (1.0 + ((other.mu - rating.mu) / c).exp());
+            let score = match rank.cmp(other_rank) {
+                std::cmp::Ordering::Less => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Greater => 0.0,
+            };
+            omega += variance / c * (score - p);
+            delta += rating.sigma / c * variance / (c * c) * p * (1.0 - p);
+        }
+        Rating {
+            mu: rating.mu + omega,
+            sigma: rating.sigma * (1.0 - delta).max(KAPPA).sqrt(),
+        }
+    }).collect()
+}
+
+/// Finishing ranks from `UnoGame::ranks`, with everyone who left sharing last place.
+pub fn game_ranks(game: &UnoGame) -> Vec<(PlayerId, usize)> {
+    let finished = game.ranks().into_iter();
+    let last = game.standings().len() + 1;
+    let dropped = game.dropped().iter().map(|player| (player.id().clone(), last));
+    finished.chain(dropped).collect()
+}
+
+pub fn rate_game(game: &UnoGame, ratings: &HashMap<PlayerId, Rating>) -> HashMap<PlayerId, Rating> {
+    let ranks = game_ranks(game);
+    let before = ranks.iter().map(|(id, _)| ratings.get(id).copied().unwrap_or_default()).collect::<Vec<Rating>>();
+    let after = rate(&before, &ranks.iter().map(|(_, rank)| *rank).collect::<Vec<usize>>());
+    ranks.into_iter().map(|(id, _)| id).zip(after).collect()
+}
+
+pub fn leaderboard(ratings: &HashMap<PlayerId, Rating>) -> Vec<(PlayerId, f64)> {
+    let mut board = ratings.iter().map(|(id, rating)| (id.clone(), rating.conservative())).collect::<Vec<(PlayerId, f64)>>();
+    board.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn winners_gain_and_uncertainty_shrinks() {
+        let after = rate(&[Rating::new(); 4], &[1, 2, 3, 4]);
+        assert!(after.windows(2).all(|pair| pair[0].mu > pair[1].mu));
+        assert!(after.iter().all(|rating| rating.sigma < SIGMA));
+        assert!((after.iter().map(|rating| rating.mu).sum::<f64>() - 4.0 * MU).abs() < 1e-9);
+
+        let tied = rate(&[Rating::new(); 3], &[1, 2, 2]);
+        assert_eq!(tied[1], tied[2]);
+    }
+
+    #[test]
+    fn rates_a_finished_game() {
+        let mut game = UnoGame::with_seed(4);
+        let host = PlayerId::from("ann");
+        for id in ["ann", "ben", "cat"] {
+            game.add_player(id, id).unwrap();
+        }
+        game.start(&host).unwrap();
+        game.kick(&host, &PlayerId::from("cat")).unwrap();
+        game.end(&host).unwrap();
+
+        let ratings = rate_game(&game, &HashMap::new());
+        let board = leaderboard(&ratings);
+        assert_eq!(board.len(), 3);
+        assert_eq!(board[0].0, *game.standings()[0].id());
+        assert_eq!(board[2].0, PlayerId::from("cat"));
+    }
+}
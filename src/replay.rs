@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use crate::history::{Action, GameLog};
+use crate::player::PlayerId;
+use crate::uno_game::UnoGame;
+
+const HELP: &str = "n/enter: next step, p: previous step, g N: jump to turn N, s N: jump to step N, end: last step
+v all: show every hand, v P2: show only P2's hand, q: quit";
+
+pub enum View {
+    Omniscient,
+    Seat(PlayerId),
+}
+
+/// Steps through a recorded game, replaying it up to the current step and caching each state.
+pub struct Viewer {
+    log: GameLog,
+    labels: Vec<(PlayerId, String)>,
+    states: HashMap<usize, UnoGame>,
+    step: usize,
+    view: View,
+}
+
+impl Viewer {
+    pub fn new(log: GameLog) -> Self {
+        let mut labels = Vec::new();
+        for entry in &log.entries {
+            if matches!(entry.action, Action::Join { .. }) && !labels.iter().any(|(id, _)| *id == entry.by) {
+                labels.push((entry.by.clone(), format!("P{}", labels.len() + 1)));
+            }
+        }
+        Viewer {
+            log,
+            labels,
+            states: HashMap::new(),
+            step: 0,
+            view: View::Omniscient,
+        }
+    }
+
+    pub fn label(&self, id: &PlayerId) -> String {
+        self.labels.iter().find(|(player, _)| player == id).map(|(_, label)| label.clone()).unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn seat(&self, name: &str) -> Option<PlayerId> {
+        self.labels.iter().find(|(id, label)| label.eq_ignore_ascii_case(name) || id.to_string() == name).map(|(id, _)| id.clone())
+    }
+
+    pub fn state(&mut self) -> Result<&UnoGame, String> {
+        if !self.states.contains_key(&self.step) {
+            let game = UnoGame::replay_to(&self.log, self.step)?;
+            self.states.insert(self.step, game);
+        }
+        Ok(&self.states[&self.step])
+    }
+
+    pub fn render(&mut self) -> Result<String, String> {
+        let total = self.log.entries.len();
+        let step = self.step;
+        let last = match step.checked_sub(1).and_then(|idx| self.log.entries.get(idx)) {
+            Some(entry) => format!("{}: {}", self.label(&entry.by), entry.action),
+            None => "(nothing yet)".to_string(),
+        };
+        let seat = match &self.view {
+            View::Omniscient => None,
+            View::Seat(id) => Some(id.clone()),
+        };
+        let labels = self.labels.clone();
+        let label = |id: &PlayerId| labels.iter().find(|(player, _)| player == id).map(|(_, label)| label.clone()).unwrap_or_else(|| id.to_string());
+        let game = self.state()?;
+        let mut out = format!("Step {}/{} - last action: {}\n", step, total, last);
+        if !game.is_started() {
+            out.push_str("The game has not started yet.\n");
+            for player in game.players() {
+                out.push_str(format!("  {} {}\n", label(player.id()), player.username()).as_str());
+            }
+            return Ok(out)
+        }
+        let top = game.get_curr_card();
+        let color = if top.color().is_empty() { "any" } else { top.get_color_name() };
+        out.push_str(format!("Discard: {} (active color {}), {} card(s) in the pile\n", top, color, game.discard_pile().len()).as_str());
+        out.push_str(format!("Deck: {} card(s) left, {} card(s) drawn so far\n\n", game.deck_size(), game.cards_drawn()).as_str());
+        for (idx, player) in game.players().enumerate() {
+            let marker = if idx == 0 { ">" } else { " " };
+            let visible = seat.as_ref().map(|seat| seat == player.id()).unwrap_or(true);
+            let hand = if visible {
+                player.cards().iter().map(|card| card.to_string()).collect::<Vec<String>>().join(", ")
+            }
+            else {
+                "hidden".to_string()
+            };
+            out.push_str(format!("{} {} {} ({} card(s)): {}\n", marker, label(player.id()), player.username(), player.cards().len(), hand).as_str());
+        }
+        for (rank, player) in (1..).zip(game.standings()) {
+            out.push_str(format!("  #{} {} {}\n", rank, label(player.id()), player.username()).as_str());
+        }
+        if game.is_over() {
+            out.push_str("\nThe game is over.\n");
+        }
+        Ok(out)
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.view = view
+    }
+
+    /// Runs one viewer command and returns what to show, or None once the viewer should quit.
+    pub fn command(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let total = self.log.entries.len();
+        match words.as_slice() {
+            [] | ["n"] | ["next"] => self.step = (self.step + 1).min(total),
+            ["p"] | ["prev"] | ["back"] => self.step = self.step.saturating_sub(1),
+            ["g", turn] | ["turn", turn] => {
+                let turn = turn.parse::<usize>().map_err(|_| format!("'{}' is not a turn number", turn))?;
+                self.step = self.log.turn_step(turn);
+            }
+            ["s", step] | ["step", step] => {
+                let step = step.parse::<usize>().map_err(|_| format!("'{}' is not a step number", step))?;
+                self.step = step.min(total);
+            }
+            ["end"] => self.step = total,
+            ["v", "all"] | ["view", "all"] => self.view = View::Omniscient,
+            ["v", seat] | ["view", seat] => {
+                let seat = self.seat(seat).ok_or_else(|| format!("Unknown seat '{}'", seat))?;
+                self.view = View::Seat(seat);
+            }
+            ["q"] | ["quit"] => return Ok(None),
+            ["h"] | ["help"] => return Ok(Some(HELP.to_string())),
+            _ => return Err(format!("Unknown command '{}', try 'help'", line.trim())),
+        }
+        self.render().map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_log() -> GameLog {
+        let mut game = UnoGame::with_seed(11);
+        let host = PlayerId::from("ann");
+        game.add_player("ann", "Ann").unwrap();
+        game.add_player("ben", "Ben").unwrap();
+        game.start(&host).unwrap();
+        for _ in 0..6 {
+            game.draw().unwrap();
+        }
+        game.log().clone()
+    }
+
+    fn hands(game: &UnoGame) -> Vec<String> {
+        game.players().map(|player| player.cards().iter().map(|card| card.to_string()).collect::<Vec<String>>().join(",")).collect()
+    }
+
+    #[test]
+    fn steps_match_the_replayed_game() {
+        let log = recorded_log();
+        let total = log.entries.len();
+        let mut viewer = Viewer::new(log.clone());
+        assert!(viewer.render().unwrap().contains("has not started"));
+
+        for step in 1..=total {
+            let out = viewer.command("n").unwrap().unwrap();
+            assert!(out.starts_with(format!("Step {}/{}", step, total).as_str()));
+            assert_eq!(hands(viewer.state().unwrap()), hands(&UnoGame::replay_to(&log, step).unwrap()));
+        }
+        viewer.command("next").unwrap();
+        assert_eq!(viewer.step(), total);
+
+        viewer.command("g 2").unwrap();
+        assert_eq!(viewer.step(), log.turn_step(2));
+        viewer.command("p").unwrap();
+        assert_eq!(viewer.step(), log.turn_step(2) - 1);
+        viewer.command("s 999").unwrap();
+        assert_eq!(viewer.step(), total);
+
+        let out = viewer.command("v p2").unwrap().unwrap();
+        assert!(out.contains("P1 Ann") && out.contains("hidden"));
+        assert!(!viewer.command("v all").unwrap().unwrap().contains("hidden"));
+        assert!(viewer.command("v P3").is_err());
+        assert!(viewer.command("g two").is_err());
+        assert!(viewer.command("jump").is_err());
+        assert!(viewer.command("q").unwrap().is_none());
+    }
+}
@@ -1,9 +1,689 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::card_input::Aliases;
+use crate::i18n::{Locale, Msg};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RuleKey {
+    Decks,
+    InitialCards,
+    DrawsSkip,
+    ReversesSkip,
+    MustPlay,
+    Callouts,
+    CalloutPenalty,
+    FalseCalloutPenalty,
+    DrawAutoplay,
+    LeaverCards,
+    AllowLateJoin,
+    LateJoinHand,
+    VoteKickThreshold,
+    VoteRuleThreshold,
+    Undo,
+    SpectatorDelay,
+    SpectatorHands,
+    MultiPlay,
+    TurnTimeout,
+    TimeBank,
+    TimeoutAction,
+    AfkLimit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleKind {
+    Boolean,
+    Integer { min: i32, max: i32 },
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RuleValue {
+    Boolean(bool),
+    Integer(i32),
+    Choice(&'static str),
+}
+
+/// A choice rule read as its own enum, so a misspelled option is a compile error instead of a silent default.
+pub trait Choice: Copy + Sized {
+    const KEY: RuleKey;
+
+    fn from_id(id: &str) -> Option<Self>;
+}
+
+macro_rules! choices {
+    ($($name:ident { $($variant:ident $id:literal),* $(,)? })*) => {
+        $(
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub enum $name {
+                $($variant),*
+            }
+
+            impl $name {
+                pub const IDS: &'static [&'static str] = &[$($id),*];
+
+                pub const fn id(self) -> &'static str {
+                    match self {
+                        $($name::$variant => $id),*
+                    }
+                }
+            }
+
+            impl Choice for $name {
+                const KEY: RuleKey = RuleKey::$name;
+
+                fn from_id(id: &str) -> Option<Self> {
+                    match id {
+                        $($id => Some($name::$variant),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+choices! {
+    LeaverCards { Deck "deck", Discard "discard" }
+    LateJoinHand { Initial "initial", Average "average", Max "max" }
+    TimeoutAction { Draw "draw", Bot "bot", Remove "remove" }
+}
+
 pub struct Rule {
-    pub(crate) idx: i32,
-    pub(crate) desc: String,
-    pub(crate) value: i32,
-    pub(crate) name:  String,
-    pub(crate) rtype: String,
-    pub(crate) max: i32,
-    pub(crate) min: i32
-}
\ No newline at end of file
+    pub key: RuleKey,
+    pub id: &'static str,
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub kind: RuleKind,
+    pub default: RuleValue,
+}
+
+pub struct Preset {
+    pub name: &'static str,
+    pub desc: &'static str,
+    pub values: &'static [(RuleKey, RuleValue)],
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        key: RuleKey::Decks,
+        id: "decks",
+        name: "Decks",
+        desc: "The number of decks to use.",
+        kind: RuleKind::Integer { min: 1, max: 8 },
+        default: RuleValue::Integer(1),
+    },
+    Rule {
+        key: RuleKey::InitialCards,
+        id: "initial_cards",
+        name: "Initial Cards",
+        desc: "How many cards to pick up at the beginning.",
+        kind: RuleKind::Integer { min: 1, max: 5000 },
+        default: RuleValue::Integer(7),
+    },
+    Rule {
+        key: RuleKey::DrawsSkip,
+        id: "draws_skip",
+        name: "Draws Skip",
+        desc: "Whether pickup cards (+2, +4) should also skip the next person's turn.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(true),
+    },
+    Rule {
+        key: RuleKey::ReversesSkip,
+        id: "reverses_skip",
+        name: "Reverses Skip",
+        desc: "Whether reverse cards skip turns when there's only two players left.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(true),
+    },
+    Rule {
+        key: RuleKey::MustPlay,
+        id: "must_play",
+        name: "Must Play",
+        desc: "Whether someone must play a card if they are able to.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::Callouts,
+        id: "callouts",
+        name: "Callouts",
+        desc: "Gives the ability to call someone out for not saying uno!",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(true),
+    },
+    Rule {
+        key: RuleKey::CalloutPenalty,
+        id: "callout_penalty",
+        name: "Callout Penalty",
+        desc: "The number of cards to give someone when called out.",
+        kind: RuleKind::Integer { min: 0, max: 1000 },
+        default: RuleValue::Integer(2),
+    },
+    Rule {
+        key: RuleKey::FalseCalloutPenalty,
+        id: "false_callout_penalty",
+        name: "False Callout Penalty",
+        desc: "The number of cards to give someone for falsely calling someone out.",
+        kind: RuleKind::Integer { min: 0, max: 1000 },
+        default: RuleValue::Integer(2),
+    },
+    Rule {
+        key: RuleKey::DrawAutoplay,
+        id: "draw_autoplay",
+        name: "Automatically Play After Draw",
+        desc: "Automatically plays a card after drawing, if possible. If a wild card is drawn, will give a prompt for color.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::LeaverCards,
+        id: "leaver_cards",
+        name: "Leaver Cards",
+        desc: "What happens to the hand of someone who leaves mid-game: shuffled back into the deck or placed under the discard pile.",
+        kind: RuleKind::Choice(LeaverCards::IDS),
+        default: RuleValue::Choice(LeaverCards::Deck.id()),
+    },
+    Rule {
+        key: RuleKey::AllowLateJoin,
+        id: "allow_late_join",
+        name: "Allow Late Join",
+        desc: "Whether players can join a game that has already started.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::LateJoinHand,
+        id: "late_join_hand",
+        name: "Late Join Hand",
+        desc: "How many cards a late joiner is dealt: Initial Cards, the average hand size at the table, or the largest hand size.",
+        kind: RuleKind::Choice(LateJoinHand::IDS),
+        default: RuleValue::Choice(LateJoinHand::Initial.id()),
+    },
+    Rule {
+        key: RuleKey::VoteKickThreshold,
+        id: "vote_kick_threshold",
+        name: "Vote Kick Threshold",
+        desc: "The percentage of players that must vote to kick someone. 0 disables vote kicks.",
+        kind: RuleKind::Integer { min: 0, max: 100 },
+        default: RuleValue::Integer(60),
+    },
+    Rule {
+        key: RuleKey::VoteRuleThreshold,
+        id: "vote_rule_threshold",
+        name: "Vote Rule Threshold",
+        desc: "The percentage of players that must vote to change a rule without the host. 0 disables rule votes.",
+        kind: RuleKind::Integer { min: 0, max: 100 },
+        default: RuleValue::Integer(0),
+    },
+    Rule {
+        key: RuleKey::Undo,
+        id: "undo",
+        name: "Undo",
+        desc: "Lets the host take back the last action, as long as nobody else has acted since.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::SpectatorDelay,
+        id: "spectator_delay",
+        name: "Spectator Delay",
+        desc: "How many turns behind the game spectators watch. 0 shows the game live.",
+        kind: RuleKind::Integer { min: 0, max: 100 },
+        default: RuleValue::Integer(0),
+    },
+    Rule {
+        key: RuleKey::SpectatorHands,
+        id: "spectator_hands",
+        name: "Spectator Hands",
+        desc: "Whether spectators see every hand. Only applies with a Spectator Delay, so hands are never shown live.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::MultiPlay,
+        id: "multi_play",
+        name: "Multi-Play",
+        desc: "Lets someone play several cards with the same number or face in one turn. The first card must match the pile, the last one sets the color and action cards stack.",
+        kind: RuleKind::Boolean,
+        default: RuleValue::Boolean(false),
+    },
+    Rule {
+        key: RuleKey::TurnTimeout,
+        id: "turn_timeout",
+        name: "Turn Timeout",
+        desc: "Seconds someone has for each turn before the Timeout Action applies, 0 for no limit.",
+        kind: RuleKind::Integer { min: 0, max: 86400 },
+        default: RuleValue::Integer(0),
+    },
+    Rule {
+        key: RuleKey::TimeBank,
+        id: "time_bank",
+        name: "Time Bank",
+        desc: "Seconds of extra time each player has for the whole game, used up once a turn runs past the Turn Timeout. 0 for none.",
+        kind: RuleKind::Integer { min: 0, max: 86400 },
+        default: RuleValue::Integer(0),
+    },
+    Rule {
+        key: RuleKey::TimeoutAction,
+        id: "timeout_action",
+        name: "Timeout Action",
+        desc: "What happens when someone runs out of time: they draw a card and pass, a bot plays for them until they come back, or they draw and pass until the AFK Limit removes them.",
+        kind: RuleKind::Choice(TimeoutAction::IDS),
+        default: RuleValue::Choice(TimeoutAction::Draw.id()),
+    },
+    Rule {
+        key: RuleKey::AfkLimit,
+        id: "afk_limit",
+        name: "AFK Limit",
+        desc: "How many timeouts in a row remove someone from the game when the Timeout Action is remove.",
+        kind: RuleKind::Integer { min: 1, max: 100 },
+        default: RuleValue::Integer(3),
+    },
+];
+
+/// Rules that no longer exist. Rule files and game logs that still mention them load without them.
+const RETIRED_RULES: &[&str] = &["draw_autopass"];
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "official",
+        desc: "The rules printed in the box.",
+        values: &[],
+    },
+    Preset {
+        name: "party",
+        desc: "Relaxed rules for big casual tables.",
+        values: &[
+            (RuleKey::DrawsSkip, RuleValue::Boolean(false)),
+            (RuleKey::CalloutPenalty, RuleValue::Integer(4)),
+            (RuleKey::FalseCalloutPenalty, RuleValue::Integer(1)),
+            (RuleKey::AllowLateJoin, RuleValue::Boolean(true)),
+            (RuleKey::LateJoinHand, RuleValue::Choice(LateJoinHand::Average.id())),
+            (RuleKey::VoteKickThreshold, RuleValue::Integer(50)),
+            (RuleKey::VoteRuleThreshold, RuleValue::Integer(50)),
+            (RuleKey::Undo, RuleValue::Boolean(true)),
+        ],
+    },
+    Preset {
+        name: "chaos",
+        desc: "Two decks, big hands and heavy penalties.",
+        values: &[
+            (RuleKey::Decks, RuleValue::Integer(2)),
+            (RuleKey::InitialCards, RuleValue::Integer(10)),
+            (RuleKey::ReversesSkip, RuleValue::Boolean(false)),
+            (RuleKey::CalloutPenalty, RuleValue::Integer(4)),
+            (RuleKey::FalseCalloutPenalty, RuleValue::Integer(4)),
+            (RuleKey::DrawAutoplay, RuleValue::Boolean(true)),
+            (RuleKey::AllowLateJoin, RuleValue::Boolean(true)),
+            (RuleKey::LateJoinHand, RuleValue::Choice(LateJoinHand::Max.id())),
+        ],
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleFormat {
+    Toml,
+    Json,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preset: Option<String>,
+    #[serde(default)]
+    rules: BTreeMap<String, RawRuleValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aliases: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawRuleValue {
+    Boolean(bool),
+    Integer(i64),
+    Text(String),
+}
+
+impl RuleKey {
+    pub fn rule(&self) -> &'static Rule {
+        RULES.iter().find(|rule| rule.key == *self).unwrap()
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.rule().id
+    }
+}
+
+impl Rule {
+    pub fn find(name: &str) -> Option<&'static Rule> {
+        let name = name.trim();
+        RULES.iter().find(|rule| rule.id.eq_ignore_ascii_case(name) || rule.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn validate(&self, value: RuleValue) -> Result<RuleValue, Msg> {
+        match (self.kind, value) {
+            (RuleKind::Boolean, RuleValue::Boolean(_)) => Ok(value),
+            (RuleKind::Integer { min, max }, RuleValue::Integer(int)) => {
+                if int > max || int < min {
+                    Err(Msg::RuleOutOfBounds { value: int.to_string(), rule: self.name.to_string(), min, max })
+                }
+                else {
+                    Ok(value)
+                }
+            }
+            (RuleKind::Choice(choices), RuleValue::Choice(choice)) => {
+                choices.iter().find(|option| option.eq_ignore_ascii_case(choice))
+                    .map(|option| RuleValue::Choice(option))
+                    .ok_or_else(|| Msg::RuleChoice { rule: self.name.to_string(), choices: choices.join(", ") })
+            }
+            _ => Err(Msg::RuleWrongKind { rule: self.name.to_string(), kind: self.kind.to_string() }),
+        }
+    }
+
+    pub fn parse(&self, value: &str) -> Result<RuleValue, Msg> {
+        let value = value.trim();
+        match self.kind {
+            RuleKind::Boolean => match value.to_lowercase().as_str() {
+                "1" | "true" | "on" | "yes" => Ok(RuleValue::Boolean(true)),
+                "0" | "false" | "off" | "no" => Ok(RuleValue::Boolean(false)),
+                _ => Err(Msg::RuleOnOff { rule: self.name.to_string() }),
+            },
+            RuleKind::Integer { .. } => match value.parse::<i32>() {
+                Ok(int) => self.validate(RuleValue::Integer(int)),
+                Err(_) => Err(Msg::RuleWholeNumber { rule: self.name.to_string() }),
+            },
+            RuleKind::Choice(choices) => match choices.iter().find(|option| option.eq_ignore_ascii_case(value)) {
+                Some(option) => Ok(RuleValue::Choice(option)),
+                None => Err(Msg::RuleChoice { rule: self.name.to_string(), choices: choices.join(", ") }),
+            },
+        }
+    }
+}
+
+impl RuleFormat {
+    pub fn detect(input: &str) -> RuleFormat {
+        if input.trim_start().starts_with('{') {
+            RuleFormat::Json
+        }
+        else {
+            RuleFormat::Toml
+        }
+    }
+}
+
+impl Preset {
+    pub fn find(name: &str) -> Option<&'static Preset> {
+        PRESETS.iter().find(|preset| preset.name.eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+impl Display for RuleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleKind::Boolean => write!(f, "on/off"),
+            RuleKind::Integer { min, max } => write!(f, "a number from {} to {}", min, max),
+            RuleKind::Choice(choices) => write!(f, "one of {}", choices.join(", ")),
+        }
+    }
+}
+
+impl Display for RuleValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleValue::Boolean(true) => write!(f, "on"),
+            RuleValue::Boolean(false) => write!(f, "off"),
+            RuleValue::Integer(int) => write!(f, "{}", int),
+            RuleValue::Choice(choice) => write!(f, "{}", choice),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Rules {
+    values: HashMap<RuleKey, RuleValue>,
+    aliases: Aliases,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::new()
+    }
+}
+
+impl Rules {
+    pub fn new() -> Self {
+        Rules {
+            values: RULES.iter().map(|rule| (rule.key, rule.default)).collect(),
+            aliases: Aliases::default(),
+        }
+    }
+
+    pub fn get(&self, key: RuleKey) -> RuleValue {
+        self.values[&key]
+    }
+
+    pub fn bool(&self, key: RuleKey) -> bool {
+        match self.get(key) {
+            RuleValue::Boolean(value) => value,
+            value => panic!("Rule {} is not a boolean ({})", key.id(), value),
+        }
+    }
+
+    pub fn int(&self, key: RuleKey) -> i32 {
+        match self.get(key) {
+            RuleValue::Integer(value) => value,
+            value => panic!("Rule {} is not an integer ({})", key.id(), value),
+        }
+    }
+
+    pub fn choice<T: Choice>(&self) -> T {
+        match self.get(T::KEY) {
+            RuleValue::Choice(value) => T::from_id(value).unwrap(),
+            value => panic!("Rule {} is not a choice ({})", T::KEY.id(), value),
+        }
+    }
+
+    pub fn set(&mut self, key: RuleKey, value: RuleValue) -> Result<(), Msg> {
+        let value = key.rule().validate(value)?;
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    pub fn apply_preset(&mut self, preset: &Preset) {
+        self.values = Rules::new().values;
+        for (key, value) in preset.values {
+            self.values.insert(*key, *value);
+        }
+    }
+
+    pub fn aliases(&self) -> &Aliases {
+        &self.aliases
+    }
+
+    pub fn aliases_mut(&mut self) -> &mut Aliases {
+        &mut self.aliases
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Rule, RuleValue)> + '_ {
+        RULES.iter().map(|rule| (rule, self.get(rule.key)))
+    }
+
+    pub fn export(&self, format: RuleFormat) -> String {
+        let mut file = RuleFile::default();
+        for (rule, value) in self.iter() {
+            let raw = match value {
+                RuleValue::Boolean(value) => RawRuleValue::Boolean(value),
+                RuleValue::Integer(value) => RawRuleValue::Integer(value as i64),
+                RuleValue::Choice(value) => RawRuleValue::Text(value.to_string()),
+            };
+            file.rules.insert(rule.id.to_string(), raw);
+        }
+        file.aliases = Some(self.aliases.iter().map(|(alias, card)| (alias.to_string(), card.to_string())).collect());
+        match format {
+            RuleFormat::Toml => toml::to_string_pretty(&file).unwrap(),
+            RuleFormat::Json => serde_json::to_string_pretty(&file).unwrap(),
+        }
+    }
+
+    pub fn import(&self, input: &str, locale: Locale) -> Result<Rules, Vec<String>> {
+        let file: RuleFile = match RuleFormat::detect(input) {
+            RuleFormat::Toml => toml::from_str(input).map_err(|err| vec![Msg::InvalidToml { error: err.to_string() }.text(locale)])?,
+            RuleFormat::Json => serde_json::from_str(input).map_err(|err| vec![Msg::InvalidJson { error: err.to_string() }.text(locale)])?,
+        };
+        let mut rules = self.clone();
+        let mut errors = Vec::new();
+        if let Some(name) = file.preset {
+            match Preset::find(name.as_str()) {
+                Some(preset) => rules.apply_preset(preset),
+                None => errors.push(Msg::PresetNotFound { preset: name }.text(locale)),
+            }
+        }
+        for (key, raw) in file.rules {
+            if RETIRED_RULES.contains(&key.as_str()) {
+                continue
+            }
+            let rule = match Rule::find(key.as_str()) {
+                Some(rule) => rule,
+                None => {
+                    errors.push(Msg::RuleNotFound { rule: key }.text(locale));
+                    continue
+                }
+            };
+            let value = match raw {
+                RawRuleValue::Boolean(value) => rule.validate(RuleValue::Boolean(value)),
+                RawRuleValue::Integer(value) => match (i32::try_from(value), rule.kind) {
+                    (Ok(value), _) => rule.validate(RuleValue::Integer(value)),
+                    (Err(_), RuleKind::Integer { min, max }) => Err(Msg::RuleOutOfBounds { value: value.to_string(), rule: rule.name.to_string(), min, max }),
+                    (Err(_), kind) => Err(Msg::RuleWrongKind { rule: rule.name.to_string(), kind: kind.to_string() }),
+                },
+                RawRuleValue::Text(value) => rule.parse(value.as_str()),
+            };
+            match value {
+                Ok(value) => {
+                    rules.values.insert(rule.key, value);
+                }
+                Err(err) => errors.push(format!("{}: {}", key, err.text(locale))),
+            }
+        }
+        if let Some(aliases) = file.aliases {
+            rules.aliases = Aliases::empty();
+            for (alias, card) in aliases {
+                if let Err(err) = rules.aliases.add(alias.as_str(), card.as_str()) {
+                    errors.push(format!("aliases.{}: {}", alias, err.text(locale)));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(rules)
+        }
+        else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choice_matches_rule<T: Choice + std::fmt::Debug + PartialEq>(ids: &'static [&'static str], id: fn(T) -> &'static str) {
+        assert_eq!(T::KEY.rule().kind, RuleKind::Choice(ids));
+        let mut rules = Rules::new();
+        for option in ids {
+            let value = T::from_id(option).unwrap();
+            assert_eq!(id(value), *option);
+            rules.set(T::KEY, RuleValue::Choice(option)).unwrap();
+            assert_eq!(rules.choice::<T>(), value);
+        }
+    }
+
+    #[test]
+    fn choice_rules_read_as_their_enums() {
+        choice_matches_rule(LeaverCards::IDS, LeaverCards::id);
+        choice_matches_rule(LateJoinHand::IDS, LateJoinHand::id);
+        choice_matches_rule(TimeoutAction::IDS, TimeoutAction::id);
+        assert_eq!(Rules::new().choice::<TimeoutAction>(), TimeoutAction::Draw);
+    }
+
+    #[test]
+    fn parses_and_validates_values() {
+        let decks = Rule::find(" Decks ").unwrap();
+        assert!(Rule::find("must_play").unwrap().key == RuleKey::MustPlay);
+        assert!(Rule::find("draw_autopass").is_none());
+        assert_eq!(decks.parse("3").ok(), Some(RuleValue::Integer(3)));
+        assert_eq!(decks.parse("9").err().unwrap().key(), "rule_out_of_bounds");
+        assert_eq!(decks.parse("two").err().unwrap().key(), "rule_whole_number");
+        assert_eq!(decks.validate(RuleValue::Boolean(true)).err().unwrap().key(), "rule_wrong_kind");
+
+        let must_play = RuleKey::MustPlay.rule();
+        assert_eq!(must_play.parse("ON").ok(), Some(RuleValue::Boolean(true)));
+        assert_eq!(must_play.parse("no").ok(), Some(RuleValue::Boolean(false)));
+        assert_eq!(must_play.parse("maybe").err().unwrap().text(Locale::Es), "El valor de la regla Must Play debe ser on u off");
+
+        let leaver = RuleKey::LeaverCards.rule();
+        assert_eq!(leaver.parse("Discard").ok(), Some(RuleValue::Choice("discard")));
+        assert_eq!(leaver.validate(RuleValue::Choice("DECK")).ok(), Some(RuleValue::Choice("deck")));
+        assert_eq!(leaver.parse("burn").err().unwrap().key(), "rule_choice");
+
+        let mut rules = Rules::new();
+        assert!(rules.set(RuleKey::InitialCards, RuleValue::Integer(0)).is_err());
+        assert!(rules.set(RuleKey::Callouts, RuleValue::Integer(1)).is_err());
+        assert_eq!(rules.int(RuleKey::InitialCards), 7);
+        rules.set(RuleKey::InitialCards, RuleValue::Integer(10)).unwrap();
+        assert_eq!(rules.int(RuleKey::InitialCards), 10);
+    }
+
+    #[test]
+    fn imports_skip_retired_rules() {
+        let rules = Rules::new().import("[rules]\ndraw_autopass = true\ndecks = 2\n", Locale::En).ok().unwrap();
+        assert_eq!(rules.int(RuleKey::Decks), 2);
+    }
+
+    #[test]
+    fn presets_reset_everything_else() {
+        assert!(Preset::find(" Party ").unwrap().name == "party");
+        assert!(Preset::find("casual").is_none());
+
+        let mut rules = Rules::new();
+        rules.set(RuleKey::MustPlay, RuleValue::Boolean(true)).unwrap();
+        rules.apply_preset(Preset::find("chaos").unwrap());
+        assert_eq!(rules.int(RuleKey::Decks), 2);
+        assert_eq!(rules.choice::<LateJoinHand>(), LateJoinHand::Max);
+        assert!(!rules.bool(RuleKey::MustPlay));
+        rules.apply_preset(Preset::find("official").unwrap());
+        assert!(RULES.iter().all(|rule| rules.get(rule.key) == rule.default));
+    }
+
+    #[test]
+    fn exports_round_trip_and_imports_report_every_error() {
+        let mut rules = Rules::new();
+        rules.apply_preset(Preset::find("party").unwrap());
+        rules.set(RuleKey::TimeoutAction, RuleValue::Choice("bot")).unwrap();
+        for format in [RuleFormat::Toml, RuleFormat::Json] {
+            let exported = rules.export(format);
+            assert_eq!(RuleFormat::detect(exported.as_str()), format);
+            let imported = Rules::new().import(exported.as_str(), Locale::En).ok().unwrap();
+            assert!(RULES.iter().all(|rule| imported.get(rule.key) == rules.get(rule.key)));
+        }
+
+        let preset = Rules::new().import("preset = \"chaos\"\n[rules]\ndecks = 3\n", Locale::En).ok().unwrap();
+        assert_eq!(preset.int(RuleKey::Decks), 3);
+        assert_eq!(preset.int(RuleKey::InitialCards), 10);
+
+        let invalid = |input: &str| Rules::new().import(input, Locale::En).err().unwrap();
+        assert!(invalid("[rules\n")[0].starts_with("Invalid TOML"));
+        assert!(invalid("{\"rules\": ")[0].starts_with("Invalid JSON"));
+        assert_eq!(invalid("colour = \"red\"\n").len(), 1);
+        let errors = invalid("preset = \"casual\"\n[rules]\nshuffle = true\ndecks = 9\ninitial_cards = 99999999999\nmust_play = \"maybe\"\n");
+        assert_eq!(errors.len(), 5);
+        assert!(errors[0].contains("casual"));
+        assert!(Rules::new().import("[rules\n", Locale::Pt).err().unwrap()[0].starts_with("TOML inválido"));
+        assert!(errors.iter().any(|err| err.contains("shuffle")));
+        assert!(errors.iter().any(|err| err.starts_with("decks:")));
+        assert!(errors.iter().any(|err| err.starts_with("initial_cards:")));
+        assert!(errors.iter().any(|err| err.starts_with("must_play:")));
+    }
+}
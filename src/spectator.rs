@@ -0,0 +1,79 @@
+use crate::player::PlayerId;
+
+#[derive(Clone)]
+pub struct Spectator {
+    pub(crate) id: PlayerId,
+    pub(crate) username: String,
+    pub(crate) seen: usize,
+}
+
+impl Spectator {
+    pub(crate) fn new(id: PlayerId, username: String) -> Self {
+        Spectator {
+            id,
+            username,
+            seen: 0,
+        }
+    }
+
+    pub fn id(&self) -> &PlayerId {
+        &self.id
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::uno_game::UnoGame;
+    use super::*;
+
+    fn game_with_spectator(delay: &str) -> (UnoGame, PlayerId) {
+        let mut game = UnoGame::with_seed(11);
+        let host = PlayerId::from("alice");
+        let watcher = PlayerId::from("dave");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_player("bob", "Bob").unwrap();
+        game.add_spectator("dave", "Dave").unwrap();
+        game.set_rule(&host, "spectator_delay", delay).unwrap();
+        game.set_rule(&host, "spectator_hands", "on").unwrap();
+        game.start(&host).unwrap();
+        (game, watcher)
+    }
+
+    #[test]
+    fn spectators_never_see_hands_live() {
+        let (mut game, watcher) = game_with_spectator("0");
+        for _ in 0..6 {
+            game.draw().unwrap();
+        }
+        let seen = game.spectator_messages(&watcher).unwrap().join("\n") + game.spectate().unwrap().as_str();
+        assert!(seen.contains("Bob drew 1 card(s)"));
+        for player in game.players() {
+            for card in player.cards() {
+                assert!(!seen.contains(card.to_string().as_str()), "{} leaked", card);
+            }
+        }
+        assert!(game.spectator_messages(&watcher).unwrap().is_empty());
+        assert!(game.add_spectator("alice", "Alice").is_err());
+    }
+
+    #[test]
+    fn delayed_spectators_watch_turns_behind() {
+        let (mut game, watcher) = game_with_spectator("2");
+        game.draw().unwrap();
+        assert!(game.spectate().unwrap().starts_with("Spectators are 2 turn(s) behind"));
+        assert!(game.spectator_messages(&watcher).unwrap().iter().all(|msg| !msg.contains("drew")));
+        game.draw().unwrap();
+        game.draw().unwrap();
+        let past = UnoGame::replay_to(game.log(), game.log().turn_step(1)).unwrap();
+        let view = game.spectate().unwrap();
+        assert!(view.starts_with(past.table().as_str()));
+        let first = past.players().last().unwrap();
+        let hand = first.cards().iter().map(|card| card.to_string()).collect::<Vec<String>>().join(", ");
+        assert!(view.contains(format!("{}: {}", first.username(), hand).as_str()));
+        assert_eq!(game.spectator_messages(&watcher).unwrap().iter().filter(|msg| msg.contains("drew")).count(), 1);
+    }
+}
@@ -0,0 +1,8 @@
+use crate::moves::Move;
+use crate::player::PlayerId;
+use crate::uno_game::UnoGame;
+
+pub trait Strategy {
+    fn name(&self) -> String;
+    fn choose(&mut self, game: &UnoGame, player: &PlayerId) -> Option<Move>;
+}
@@ -0,0 +1,527 @@
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::observer::Observer;
+use crate::player::PlayerId;
+use crate::rating::{self, Rating};
+use crate::rules::Rules;
+use crate::strategy::Strategy;
+use crate::uno_game::UnoGame;
+
+const MAX_MOVES: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    RoundRobin,
+    Swiss { rounds: usize },
+    Knockout,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// One point for every player finishing below you.
+    Placement,
+    /// The first player out scores the cards left in everyone else's hand.
+    Points,
+}
+
+pub struct Participant {
+    pub id: PlayerId,
+    pub name: String,
+    pub rating: Rating,
+    strategy: Option<Box<dyn Strategy>>,
+}
+
+impl Participant {
+    pub fn human(id: impl Into<PlayerId>, name: &str) -> Self {
+        Participant {
+            id: id.into(),
+            name: name.to_string(),
+            rating: Rating::new(),
+            strategy: None,
+        }
+    }
+
+    pub fn bot(id: impl Into<PlayerId>, name: &str, strategy: Box<dyn Strategy>) -> Self {
+        Participant {
+            strategy: Some(strategy),
+            ..Participant::human(id, name)
+        }
+    }
+
+    pub fn with_rating(mut self, rating: Rating) -> Self {
+        self.rating = rating;
+        self
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.strategy.is_some()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableResult {
+    pub round: usize,
+    pub order: Vec<PlayerId>,
+    /// The rank of each player in `order`, shared by players who tied.
+    pub ranks: Vec<usize>,
+    pub scores: Vec<(PlayerId, i32)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub id: PlayerId,
+    pub name: String,
+    pub score: i32,
+    pub wins: usize,
+    pub games: usize,
+    pub byes: usize,
+    /// Sum of the scores of everyone this player sat with.
+    pub buchholz: i32,
+    pub rating: f64,
+    /// The knockout round this player went out in.
+    pub eliminated: Option<usize>,
+}
+
+struct FirstOut(Rc<Cell<i32>>);
+
+impl Observer for FirstOut {
+    fn on_player_finished(&mut self, game: &UnoGame, player: &PlayerId, rank: usize) {
+        if rank == 1 {
+            let left = game.standings().iter().chain(game.players()).filter(|other| other.id() != player);
+            self.0.set(left.flat_map(|other| other.cards()).map(|card| card.points()).sum());
+        }
+    }
+}
+
+pub struct Tournament {
+    format: Format,
+    scoring: Scoring,
+    table_size: usize,
+    seed: u64,
+    rules: Rules,
+    participants: Vec<Participant>,
+    results: Vec<TableResult>,
+    met: HashMap<(usize, usize), usize>,
+    eliminated: HashMap<usize, usize>,
+}
+
+impl Tournament {
+    pub fn new(format: Format, participants: Vec<Participant>) -> Self {
+        Tournament {
+            format,
+            scoring: Scoring::Placement,
+            table_size: 4,
+            seed: 0,
+            rules: Rules::new(),
+            participants,
+            results: Vec::new(),
+            met: HashMap::new(),
+            eliminated: HashMap::new(),
+        }
+    }
+
+    pub fn with_scoring(mut self, scoring: Scoring) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    pub fn with_table_size(mut self, size: usize) -> Self {
+        self.table_size = size.max(2);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn results(&self) -> &[TableResult] {
+        &self.results
+    }
+
+    pub fn participants(&self) -> &[Participant] {
+        &self.participants
+    }
+
+    /// Plays every round. Bot-only tables are simulated, any table with a human is handed to `humans`
+    /// after it has started and is ended by the host if it is still running when the callback returns.
+    pub fn run(&mut self, humans: &mut dyn FnMut(&mut UnoGame)) -> Result<(), String> {
+        if self.participants.len() < 2 {
+            return Err("A tournament needs at least 2 participants".to_string())
+        }
+        match self.format {
+            Format::RoundRobin => {
+                let everyone = (0..self.participants.len()).collect::<Vec<usize>>();
+                for (round, tables) in (1..).zip(round_robin(&everyone, self.table_size)) {
+                    for table in tables {
+                        self.play_table(round, &table, humans)?;
+                    }
+                }
+            }
+            Format::Swiss { rounds } => {
+                for round in 1..=rounds {
+                    let order = self.ranked();
+                    for table in self.swiss_tables(&order) {
+                        self.play_table(round, &table, humans)?;
+                    }
+                }
+            }
+            Format::Knockout => {
+                let mut alive = (0..self.participants.len()).collect::<Vec<usize>>();
+                let mut round = 1;
+                while alive.len() > 1 {
+                    alive.sort_by(|a, b| self.participants[*b].rating.conservative().total_cmp(&self.participants[*a].rating.conservative()));
+                    let mut next = Vec::new();
+                    for table in split(&alive, self.table_size) {
+                        let result = self.play_table(round, &table, humans)?;
+                        // A shared first place goes to the higher rated player.
+                        let winner = table.iter().copied()
+                            .filter(|seat| result.order.iter().zip(&result.ranks).any(|(id, rank)| *id == self.participants[*seat].id && *rank == 1))
+                            .max_by(|a, b| {
+                                let (a, b) = (&self.participants[*a], &self.participants[*b]);
+                                a.rating.conservative().total_cmp(&b.rating.conservative()).then(b.id.cmp(&a.id))
+                            });
+                        for seat in table {
+                            if Some(seat) == winner {
+                                next.push(seat);
+                            }
+                            else {
+                                self.eliminated.insert(seat, round);
+                            }
+                        }
+                    }
+                    alive = next;
+                    round += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn play_table(&mut self, round: usize, table: &[usize], humans: &mut dyn FnMut(&mut UnoGame)) -> Result<TableResult, String> {
+        if let [seat] = table {
+            let id = self.participants[*seat].id.clone();
+            let bye = match self.scoring {
+                Scoring::Placement => self.table_size as i32 - 1,
+                Scoring::Points => 0,
+            };
+            let result = TableResult { round, order: vec![id.clone()], ranks: vec![1], scores: vec![(id, bye)] };
+            self.results.push(result.clone());
+            return Ok(result)
+        }
+        let mut game = UnoGame::from_log(self.seed.wrapping_add(self.results.len() as u64), self.rules.clone());
+        for seat in table {
+            let participant = &self.participants[*seat];
+            game.add_player(participant.id.clone(), participant.name.as_str())?;
+        }
+        let points = Rc::new(Cell::new(0));
+        game.add_observer(Box::new(FirstOut(points.clone())));
+        let host = self.participants[table[0]].id.clone();
+        game.start(&host)?;
+        if table.iter().all(|seat| self.participants[*seat].is_bot()) {
+            self.simulate(&mut game, table)?;
+        }
+        else {
+            humans(&mut game);
+        }
+        if !game.is_over() {
+            let host = game.host().cloned().unwrap_or(host);
+            game.end(&host)?;
+        }
+
+        let ranks = rating::game_ranks(&game);
+        let order = ranks.iter().map(|(id, _)| id.clone()).collect::<Vec<PlayerId>>();
+        let places = ranks.iter().map(|(_, rank)| *rank).collect::<Vec<usize>>();
+        let scores = ranks.iter().map(|(id, rank)| {
+            let score = match self.scoring {
+                Scoring::Placement => table.len().saturating_sub(*rank) as i32,
+                Scoring::Points if *rank == 1 => points.get(),
+                Scoring::Points => 0,
+            };
+            (id.clone(), score)
+        }).collect::<Vec<(PlayerId, i32)>>();
+        let ratings = table.iter().map(|seat| (self.participants[*seat].id.clone(), self.participants[*seat].rating)).collect();
+        for (id, rating) in rating::rate_game(&game, &ratings) {
+            if let Some(participant) = self.participants.iter_mut().find(|participant| participant.id == id) {
+                participant.rating = rating;
+            }
+        }
+        for (idx, a) in table.iter().enumerate() {
+            for b in &table[idx + 1..] {
+                *self.met.entry((*a.min(b), *a.max(b))).or_default() += 1;
+            }
+        }
+        let result = TableResult { round, order, ranks: places, scores };
+        self.results.push(result.clone());
+        Ok(result)
+    }
+
+    fn simulate(&mut self, game: &mut UnoGame, table: &[usize]) -> Result<(), String> {
+        for _ in 0..MAX_MOVES {
+            if game.is_over() {
+                break
+            }
+            let id = game.get_curr_player().id().clone();
+            let seat = table.iter()
+                .copied()
+                .find(|seat| self.participants[*seat].id == id)
+                .ok_or_else(|| format!("{} is not seated at this table", id))?;
+            let strategy = self.participants[seat].strategy.as_mut().ok_or_else(|| format!("{} is not a bot", id))?;
+            let play = strategy.choose(game, &id).ok_or_else(|| format!("{} has no move", id))?;
+            game.make_move(&play)?;
+            if game.get_player(&id).is_some_and(|player| player.cards().len() == 1) {
+                game.uno(&id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn meetings(&self, a: usize, b: usize) -> usize {
+        self.met.get(&(a.min(b), a.max(b))).copied().unwrap_or(0)
+    }
+
+    /// Seats players in standings order, filling each table with whoever has met its players the least.
+    /// A bye goes to the lowest ranked player with the fewest byes so far.
+    fn swiss_tables(&self, order: &[usize]) -> Vec<Vec<usize>> {
+        let mut left = order.to_vec();
+        let mut sizes = table_sizes(order.len(), self.table_size);
+        let mut bye = None;
+        if sizes.last() == Some(&1) {
+            sizes.pop();
+            let byes = |seat: &usize| self.results.iter().filter(|result| result.order == [self.participants[*seat].id.clone()]).count();
+            let (idx, _) = left.iter().enumerate().rev().min_by_key(|(_, seat)| byes(seat)).unwrap();
+            bye = Some(vec![left.remove(idx)]);
+        }
+        let mut tables = Vec::new();
+        for size in sizes {
+            let mut table = vec![left.remove(0)];
+            while table.len() < size {
+                let (idx, _) = left.iter().enumerate()
+                    .min_by_key(|(idx, seat)| (table.iter().map(|other| self.meetings(**seat, *other)).sum::<usize>(), *idx))
+                    .unwrap();
+                table.push(left.remove(idx));
+            }
+            tables.push(table);
+        }
+        tables.extend(bye);
+        tables
+    }
+
+    fn ranked(&self) -> Vec<usize> {
+        let standings = self.standings();
+        let mut order = (0..self.participants.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|seat| standings.iter().position(|standing| standing.id == self.participants[*seat].id));
+        order
+    }
+
+    /// Ordered by knockout round reached, score, wins, Buchholz, then rating.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = self.participants.iter().enumerate().map(|(seat, participant)| Standing {
+            id: participant.id.clone(),
+            name: participant.name.clone(),
+            score: 0,
+            wins: 0,
+            games: 0,
+            byes: 0,
+            buchholz: 0,
+            rating: participant.rating.conservative(),
+            eliminated: self.eliminated.get(&seat).copied(),
+        }).collect::<Vec<Standing>>();
+        let find = |standings: &[Standing], id: &PlayerId| standings.iter().position(|standing| standing.id == *id);
+        for result in &self.results {
+            for (rank, (id, score)) in result.ranks.iter().zip(&result.scores) {
+                let Some(idx) = find(&standings, id) else {
+                    continue
+                };
+                let standing = &mut standings[idx];
+                standing.score += score;
+                if result.order.len() == 1 {
+                    standing.byes += 1;
+                }
+                else {
+                    standing.games += 1;
+                    if *rank == 1 {
+                        standing.wins += 1;
+                    }
+                }
+            }
+        }
+        let totals = standings.iter().map(|standing| (standing.id.clone(), standing.score)).collect::<HashMap<PlayerId, i32>>();
+        for result in self.results.iter().filter(|result| result.order.len() > 1) {
+            for id in &result.order {
+                let others = result.order.iter().filter(|other| *other != id).map(|other| totals[other]).sum::<i32>();
+                if let Some(idx) = find(&standings, id) {
+                    standings[idx].buchholz += others;
+                }
+            }
+        }
+        standings.sort_by(|a, b| {
+            b.eliminated.map_or(usize::MAX, |round| round).cmp(&a.eliminated.map_or(usize::MAX, |round| round))
+                .then(b.score.cmp(&a.score))
+                .then(b.wins.cmp(&a.wins))
+                .then(b.buchholz.cmp(&a.buchholz))
+                .then(b.rating.total_cmp(&a.rating))
+                .then(a.id.cmp(&b.id))
+        });
+        standings
+    }
+
+    pub fn report(&self) -> String {
+        let format = match self.format {
+            Format::RoundRobin => "Round robin".to_string(),
+            Format::Swiss { rounds } => format!("Swiss, {} round(s)", rounds),
+            Format::Knockout => "Knockout".to_string(),
+        };
+        let name = |id: &PlayerId| self.participants.iter().find(|participant| participant.id == *id).map(|participant| participant.name.clone()).unwrap_or_else(|| id.to_string());
+        let mut out = format!("{} with {} players\n", format, self.participants.len());
+        let rounds = self.results.iter().map(|result| result.round).max().unwrap_or(0);
+        for round in 1..=rounds {
+            let tables = self.results.iter()
+                .filter(|result| result.round == round)
+                .map(|result| match result.order.as_slice() {
+                    [id] => format!("{} (bye)", name(id)),
+                    order => {
+                        let mut text = name(&order[0]);
+                        for (idx, id) in order.iter().enumerate().skip(1) {
+                            text.push_str(if result.ranks[idx] == result.ranks[idx - 1] { " = " } else { " > " });
+                            text.push_str(name(id).as_str());
+                        }
+                        text
+                    }
+                })
+                .collect::<Vec<String>>();
+            out.push_str(format!("\nRound {}: {}", round, tables.join(" | ")).as_str());
+        }
+        out.push('\n');
+        for (rank, standing) in (1..).zip(self.standings()) {
+            out.push_str(format!("\n{}. {} - {} pts, {} win(s) in {} game(s), Buchholz {}, rating {:.1}", rank, standing.name, standing.score, standing.wins, standing.games, standing.buchholz, standing.rating).as_str());
+            if let Some(round) = standing.eliminated {
+                out.push_str(format!(", out in round {}", round).as_str());
+            }
+        }
+        out
+    }
+}
+
+/// Splits players into tables of at most `size`, as even as possible. A table of one is a bye.
+fn table_sizes(players: usize, size: usize) -> Vec<usize> {
+    let tables = players.div_ceil(size);
+    (0..tables).map(|idx| players / tables + usize::from(idx < players % tables)).collect()
+}
+
+fn split(players: &[usize], size: usize) -> Vec<Vec<usize>> {
+    let mut rest = players;
+    table_sizes(players.len(), size).into_iter().map(|size| {
+        let (table, tail) = rest.split_at(size);
+        rest = tail;
+        table.to_vec()
+    }).collect()
+}
+
+/// Rounds where nobody plays twice, until everyone has sat with everyone else at least once.
+/// Each seat goes to whoever meets the most new opponents at that table, which keeps
+/// the schedule to a few rounds instead of one table for every possible group.
+fn round_robin(players: &[usize], size: usize) -> Vec<Vec<Vec<usize>>> {
+    let count = players.len();
+    let size = size.min(count);
+    let mut met = (0..count).map(|a| (0..count).map(|b| a == b).collect::<Vec<bool>>()).collect::<Vec<Vec<bool>>>();
+    let mut rounds = Vec::new();
+    while rounds.len() < 2 * count && met.iter().flatten().any(|seen| !seen) {
+        let mut free = (0..count).collect::<Vec<usize>>();
+        let mut round = Vec::new();
+        for seats in table_sizes(count, size) {
+            let mut table: Vec<usize> = Vec::new();
+            while table.len() < seats {
+                let score = |seat: usize| (
+                    table.iter().filter(|other| !met[seat][**other]).count(),
+                    met[seat].iter().filter(|seen| !**seen).count(),
+                    Reverse(seat),
+                );
+                let pick = (0..free.len()).max_by_key(|idx| score(free[*idx])).unwrap();
+                table.push(free.remove(pick));
+            }
+            if table.len() > 1 {
+                for a in &table {
+                    for b in &table {
+                        met[*a][*b] = true;
+                    }
+                }
+                round.push(table.iter().map(|idx| players[*idx]).collect());
+            }
+        }
+        rounds.push(round);
+    }
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{BotPreset, Heuristic, RandomMoves};
+
+    fn bots(count: usize) -> Vec<Participant> {
+        (0..count).map(|idx| {
+            let strategy: Box<dyn Strategy> = if idx % 2 == 0 {
+                Box::new(RandomMoves::new(idx as u64))
+            }
+            else {
+                Box::new(Heuristic::new(BotPreset::find("medium").unwrap().personality, idx as u64))
+            };
+            Participant::bot(format!("bot{}", idx), format!("Bot {}", idx).as_str(), strategy)
+        }).collect()
+    }
+
+    #[test]
+    fn schedules_cover_everyone() {
+        assert_eq!(table_sizes(7, 4), vec![4, 3]);
+        assert_eq!(table_sizes(3, 2), vec![2, 1]);
+        for (players, size, tables) in [(5, 2, 10), (12, 4, 15), (16, 4, 20)] {
+            let everyone = (0..players).collect::<Vec<usize>>();
+            let rounds = round_robin(&everyone, size);
+            assert_eq!(rounds.iter().map(|round| round.len()).sum::<usize>(), tables);
+            for round in &rounds {
+                let mut seats = round.iter().flatten().collect::<Vec<_>>();
+                let seated = seats.len();
+                seats.sort();
+                seats.dedup();
+                assert_eq!(seats.len(), seated);
+            }
+            for (a, b) in everyone.iter().flat_map(|a| everyone.iter().filter(move |b| a < *b).map(move |b| (a, b))) {
+                assert!(rounds.iter().flatten().any(|table| table.contains(a) && table.contains(b)), "{} never met {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn formats_run_to_a_report() {
+        let mut swiss = Tournament::new(Format::Swiss { rounds: 3 }, bots(5)).with_table_size(2).with_seed(1);
+        swiss.run(&mut |_| panic!("no humans here")).unwrap();
+        let standings = swiss.standings();
+        assert_eq!(standings.iter().map(|standing| standing.games + standing.byes).sum::<usize>(), 15);
+        assert!(standings.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(standings.iter().all(|standing| standing.byes <= 1));
+        assert!(swiss.report().contains("Round 3:"));
+
+        let mut entrants = bots(4);
+        entrants.push(Participant::human("me", "Me"));
+        let mut knockout = Tournament::new(Format::Knockout, entrants).with_table_size(3).with_scoring(Scoring::Points);
+        let mut hosted = 0;
+        knockout.run(&mut |game| {
+            hosted += 1;
+            let me = PlayerId::from("me");
+            game.remove_player(&me).ok();
+        }).unwrap();
+        assert!(hosted >= 1);
+        let standings = knockout.standings();
+        assert_eq!(standings.iter().filter(|standing| standing.eliminated.is_none()).count(), 1);
+        assert!(standings[0].eliminated.is_none());
+        assert_ne!(standings[0].id, PlayerId::from("me"));
+    }
+}
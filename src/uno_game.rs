@@ -1,17 +1,25 @@
-use std::cmp::min;
-use std::collections::HashMap;
-use std::fmt::format;
-use std::time::SystemTime;
-use crate::card::Card;
-use crate::player::Player;
-use crate::rules::Rule;
-use rand::thread_rng;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use crate::bots::{Bot, BotPreset, BOT_PRESETS};
+use crate::card::{color_text, Card, COLORS};
+use crate::card_input;
+use crate::clock::{Clock, SystemClock};
+use crate::history::{Action, GameLog, LogEntry};
+use crate::i18n::{Locale, Msg};
+use crate::observer::{GameEvent, Observer};
+use crate::player::{Player, PlayerId, PlayerStats};
+use crate::rules::{LateJoinHand, LeaverCards, Preset, PRESETS, Rule, RuleFormat, RuleKey, RuleValue, Rules, TimeoutAction};
+use crate::spectator::Spectator;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use std::time::{UNIX_EPOCH};
+
+const MAX_NAME_LENGTH: usize = 32;
+const STAND_IN_PRESET: &str = "medium";
 
 pub struct UnoGame {
-    players : HashMap<i32,Player>,
-    queue: Vec<Player>,
+    players : HashMap<PlayerId,Player>,
+    queue: Vec<PlayerId>,
     deck: Vec<Card>,
     called_out: bool,
     discard: Vec<Card>,
@@ -19,14 +27,92 @@ pub struct UnoGame {
     dropped: Vec<Player>,
     started: bool,
     drawn: i32,
-    confirm: bool,
     card_num: i32,
     time_started: i64,
-    rules: [Rule; 10],
+    time_ended: Option<i64>,
+    rules: Rules,
+    host: Option<PlayerId>,
+    rules_locked: bool,
+    kick_votes: HashMap<PlayerId, HashSet<PlayerId>>,
+    rule_votes: HashMap<(RuleKey, RuleValue), HashSet<PlayerId>>,
+    rng: StdRng,
+    log: GameLog,
+    replay_clock: Option<u64>,
+    redo: Vec<LogEntry>,
+    observers: Vec<Box<dyn Observer>>,
+    bots: HashMap<PlayerId, Bot>,
+    stand_ins: HashSet<PlayerId>,
+    clock: Rc<dyn Clock>,
+    banks: HashMap<PlayerId, u64>,
+    timeouts: HashMap<PlayerId, i32>,
+    events: Vec<GameEvent>,
+    spectators: HashMap<PlayerId, Spectator>,
+    feed: Vec<(usize, String)>,
+    turns: usize,
+    started_at: u64,
+    turn_started: u64,
+    locale: Locale,
+    locales: HashMap<PlayerId, Locale>,
+}
+
+impl Default for UnoGame {
+    fn default() -> Self {
+        UnoGame::new()
+    }
+}
+
+impl Clone for UnoGame {
+    fn clone(&self) -> Self {
+        UnoGame {
+            players: self.players.clone(),
+            queue: self.queue.clone(),
+            deck: self.deck.clone(),
+            called_out: self.called_out,
+            discard: self.discard.clone(),
+            finished: self.finished.clone(),
+            dropped: self.dropped.clone(),
+            started: self.started,
+            drawn: self.drawn,
+            card_num: self.card_num,
+            time_started: self.time_started,
+            time_ended: self.time_ended,
+            rules: self.rules.clone(),
+            host: self.host.clone(),
+            rules_locked: self.rules_locked,
+            kick_votes: self.kick_votes.clone(),
+            rule_votes: self.rule_votes.clone(),
+            rng: self.rng.clone(),
+            log: self.log.clone(),
+            replay_clock: self.replay_clock,
+            redo: self.redo.clone(),
+            observers: Vec::new(),
+            bots: HashMap::new(),
+            stand_ins: HashSet::new(),
+            clock: self.clock.clone(),
+            banks: self.banks.clone(),
+            timeouts: self.timeouts.clone(),
+            events: self.events.clone(),
+            spectators: self.spectators.clone(),
+            feed: self.feed.clone(),
+            turns: self.turns,
+            started_at: self.started_at,
+            turn_started: self.turn_started,
+            locale: self.locale,
+            locales: self.locales.clone(),
+        }
+    }
 }
 
 impl UnoGame {
     pub fn new() -> UnoGame {
+        UnoGame::with_seed(thread_rng().gen())
+    }
+
+    pub fn with_seed(seed: u64) -> UnoGame {
+        UnoGame::from_log(seed, Rules::new())
+    }
+
+    pub(crate) fn from_log(seed: u64, rules: Rules) -> UnoGame {
         UnoGame {
             players: HashMap::new(),
             queue: Vec::new(),
@@ -38,40 +124,235 @@ impl UnoGame {
             drawn: 0,
             card_num: 1,
             started: false,
-            confirm: false,
             time_started: 0,
-            rules: UnoGame::generate_rules()
+            time_ended: None,
+            log: GameLog::new(seed, &rules),
+            rules,
+            host: None,
+            rules_locked: false,
+            kick_votes: HashMap::new(),
+            rule_votes: HashMap::new(),
+            rng: StdRng::seed_from_u64(seed),
+            replay_clock: None,
+            redo: Vec::new(),
+            observers: Vec::new(),
+            bots: HashMap::new(),
+            stand_ins: HashSet::new(),
+            clock: Rc::new(SystemClock),
+            banks: HashMap::new(),
+            timeouts: HashMap::new(),
+            events: Vec::new(),
+            spectators: HashMap::new(),
+            feed: Vec::new(),
+            turns: 0,
+            started_at: 0,
+            turn_started: 0,
+            locale: Locale::default(),
+            locales: HashMap::new(),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.replay_clock.unwrap_or_else(|| self.clock.now())
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Rc::new(clock);
+    }
+
+    fn minutes(&self) -> i64 {
+        (self.now() / 60000) as i64
+    }
+
+    pub(crate) fn set_replay_clock(&mut self, at: Option<u64>) {
+        self.replay_clock = at;
+    }
+
+    pub(crate) fn sandbox(&self, rng: &mut StdRng) -> UnoGame {
+        let mut game = UnoGame {
+            log: GameLog::new(self.log.seed, &self.rules),
+            redo: Vec::new(),
+            spectators: HashMap::new(),
+            feed: Vec::new(),
+            ..self.clone()
+        };
+        for player in game.players.values_mut() {
+            player.messages.clear();
+        }
+        game.rng = StdRng::seed_from_u64(rng.gen());
+        game.replay_clock = Some(self.now());
+        game
+    }
+
+    pub(crate) fn hidden_cards(&self, viewer: &PlayerId) -> Vec<Card> {
+        let mut cards = self.deck.clone();
+        for id in self.queue.iter().filter(|id| *id != viewer) {
+            cards.extend(self.players[id].hand.iter().cloned());
+        }
+        cards
+    }
+
+    pub(crate) fn set_hidden(&mut self, hands: Vec<(PlayerId, Vec<Card>)>, deck: Vec<Card>) {
+        for (id, hand) in hands {
+            if let Some(player) = self.players.get_mut(&id) {
+                player.hand = hand;
+            }
+        }
+        self.deck = deck;
+    }
+
+    fn record(&mut self, by: &PlayerId, action: Action) {
+        let at = self.now();
+        if action.is_turn() {
+            self.turns += 1;
+            let elapsed = at.saturating_sub(self.turn_started);
+            self.update_stats(by, |stats| {
+                stats.turns += 1;
+                stats.longest_turn = stats.longest_turn.max(elapsed);
+            });
+            let bank = self.rules.int(RuleKey::TimeBank) as u64 * 1000;
+            if bank > 0 {
+                let over = elapsed.saturating_sub(self.rules.int(RuleKey::TurnTimeout) as u64 * 1000);
+                let left = self.banks.entry(by.clone()).or_insert(bank);
+                *left = left.saturating_sub(over);
+            }
+            if action == Action::Timeout {
+                *self.timeouts.entry(by.clone()).or_default() += 1;
+            }
+            else {
+                self.timeouts.remove(by);
+            }
+        }
+        if action == Action::Start {
+            self.started_at = at;
         }
+        let entry = LogEntry {
+            at,
+            by: by.clone(),
+            action,
+        };
+        self.log.entries.push(entry);
+        self.redo.clear();
+        self.dispatch(at);
     }
-    pub fn start(&mut self) {
+
+    fn track(&mut self, event: &GameEvent, at: u64) {
+        match event {
+            GameEvent::TurnStart(_) => self.turn_started = at,
+            GameEvent::CardPlayed(player, card) => {
+                let action = ["SKIP", "REVERSE", "+2"].contains(&card.id.as_str());
+                self.update_stats(player, |stats| {
+                    stats.cards_played += 1;
+                    if action {
+                        stats.action_cards += 1;
+                    }
+                    if card.wild {
+                        stats.wilds_played += 1;
+                    }
+                });
+            }
+            GameEvent::Draw(player, count, false) => self.update_stats(player, |stats| stats.cards_drawn += count),
+            GameEvent::Draw(player, count, true) => self.update_stats(player, |stats| stats.penalty_cards += count),
+            GameEvent::Uno(player) => self.update_stats(player, |stats| stats.uno_calls += 1),
+            GameEvent::Callout(by, caught) if caught.is_empty() => self.update_stats(by, |stats| stats.false_callouts += 1),
+            GameEvent::Callout(by, _) => self.update_stats(by, |stats| stats.callouts += 1),
+            GameEvent::PlayerFinished(player, _) => {
+                let after = at.saturating_sub(self.started_at);
+                self.update_stats(player, |stats| stats.finished_after = Some(after));
+            }
+            GameEvent::GameEnd => {}
+        }
+    }
+
+    fn update_stats(&mut self, id: &PlayerId, update: impl Fn(&mut PlayerStats)) {
+        let copies = self.finished.iter_mut().chain(self.dropped.iter_mut()).filter(|player| player.id == *id);
+        for player in self.players.get_mut(id).into_iter().chain(copies) {
+            update(&mut player.stats);
+        }
+    }
+
+    pub fn stats(&self, id: &PlayerId) -> Option<&PlayerStats> {
+        self.players.get(id)
+            .or_else(|| self.finished.iter().chain(self.dropped.iter()).find(|player| player.id == *id))
+            .map(|player| &player.stats)
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    fn dispatch(&mut self, at: u64) {
+        let events = std::mem::take(&mut self.events);
+        for event in &events {
+            self.track(event, at);
+            self.feed.push((self.turns, event.describe(self)));
+        }
+        let mut observers = std::mem::take(&mut self.observers);
+        for event in &events {
+            for observer in observers.iter_mut() {
+                event.notify(observer.as_mut(), self);
+            }
+        }
+        self.observers = observers;
+    }
+
+    pub fn log(&self) -> &GameLog {
+        &self.log
+    }
+
+    pub fn export_log(&self) -> String {
+        self.log.to_json()
+    }
+    pub fn start(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.started {
+            return Err(self.text_for(by, Msg::AlreadyStarted))
+        }
         if self.players.len() < 2 {
-            panic!("Need atleast two players to start!")
+            return Err(self.text_for(by, Msg::NeedTwoPlayers))
         }
-        self.generate_deck();
-        for (_, player) in &self.players {
-            self.queue.push(player.clone())
+        let start_card_no = self.rules.int(RuleKey::InitialCards);
+        let decks = self.rules.int(RuleKey::Decks);
+        if start_card_no * self.players.len() as i32 >= decks * 108 {
+            return Err(self.text_for(by, Msg::NotEnoughCards))
         }
-        self.time_started = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()/60) as i64;
+        self.generate_deck();
+        self.time_started = self.minutes();
         self.discard.push(self.deck.pop().unwrap());
         self.started = true;
-        let start_card_no = self.get_rule("Initial Cards").unwrap().value;
-        if start_card_no * self.players.len() as i32 > self.deck.len() as i32 {
-            panic!("Did not find enough cards to start playing")
-        }
-        for id in 0..self.players.len() as i32{
-            self.deal(id, start_card_no);
+        self.rules_locked = true;
+        for id in self.queue.clone() {
+            self.deal(&id, start_card_no);
         }
+        self.emit(GameEvent::TurnStart(self.queue[0].clone()));
+        self.record(by, Action::Start);
+        Ok(self.text_for(by, Msg::GameStarted { table: self.table() }))
     }
 
-    fn deal(&mut self, player_id: i32, number: i32) -> i32 {
+    fn deal(&mut self, player_id: &PlayerId, number: i32) -> i32 {
+        if number <= 0 {
+            return 0
+        }
         if self.deck.len() < number as usize {
-            if self.discard.len() == 0 {
+            if self.discard.is_empty() {
                 panic!("Not enough cards found to play");
             }
-            self.generate_deck();
-            self.discard = Vec::from([self.discard[0].clone()]);
+            let top = self.discard.pop().unwrap();
+            for card in self.discard.iter_mut().filter(|card| card.wild) {
+                card.color = String::new();
+            }
+            self.deck.append(&mut self.discard);
+            self.discard = Vec::from([top]);
+            self.shuffle_deck();
+            if self.deck.len() < number as usize {
+                self.generate_deck();
+            }
         }
-        if let Some(player) = self.players.get_mut(&player_id) {
+        if let Some(player) = self.players.get_mut(player_id) {
             let card = self.deck[0].num;
             for _ in 0..number {
                 player.hand.push(self.deck[0].clone());
@@ -80,7 +361,7 @@ impl UnoGame {
             }
             player.sort_hand();
             player.called = false;
-            return card
+            card
         }
         else {
             panic!("Player with id {} not found", player_id)
@@ -89,21 +370,32 @@ impl UnoGame {
 
     pub fn scoreboard(&self) -> String {
         let mut out = String::new();
-        let mut rank = 1;
-        for person in &self.finished {
-            out.push_str(format!("{}. *{}*", rank, person.username).as_str());
-            rank+=1;
+        for ((_, rank), person) in self.ranks().into_iter().zip(self.finished.iter()) {
+            out.push_str(self.text(Msg::ScoreboardRank { rank, name: person.username.clone() }).as_str());
+            out.push('\n');
+        }
+        if !self.dropped.is_empty() {
+            let left = self.dropped.iter().map(|person| person.username.clone()).collect::<Vec<String>>().join(", ");
+            out.push_str(self.text(Msg::LeftTheGame { names: left }).as_str());
+            out.push('\n');
+        }
+        let mins = self.time_ended.unwrap_or_else(|| self.minutes()) - self.time_started;
+        out.push('\n');
+        out.push_str(self.text(Msg::GameLasted { minutes: mins, drawn: self.drawn }).as_str());
+        if self.started {
+            out.push('\n');
+            for person in self.finished.iter().chain(self.queue.iter().map(|id| &self.players[id])).chain(self.dropped.iter()) {
+                out.push_str(format!("\n*{}*: {}", person.username, person.stats.summary(self.locale)).as_str());
+            }
         }
-        let mins = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()/60) as i64 - self.time_started;
-        out.push_str(format!("\nThis game lasted {} minutes and {} cards were drawn",mins,self.drawn).as_str());
         out
     }
 
     fn generate_deck(&mut self) {
-        let decks = self.get_rule("decks");
-        if let Some(deck_no) = decks {
-            for _ in 0..deck_no.value {
-                for color in ["R","G","B","Y"] {
+        let decks = self.rules.int(RuleKey::Decks);
+        {
+            for _ in 0..decks {
+                for color in COLORS {
                     for card in 0..10 {
                         self.deck.push(Card::new(card.to_string(), color, self.card_num));
                         self.card_num+=1;
@@ -128,110 +420,406 @@ impl UnoGame {
             }
 
         }
-        else {
-            panic!("Rule 'decks' not found");
-        }
         self.shuffle_deck();
     }
 
-    pub fn add_player(&mut self, name: &str) -> &Player {
-        let player = Player {
-            id: self.players.len() as i32,
-            username: name.to_string(),
-            hand: vec![],
-            called: false,
-            finished: false,
-            cards_played: 0,
-            messages: vec![],
+    pub fn add_player(&mut self, id: impl Into<PlayerId>, name: &str) -> Result<&Player, String> {
+        self.add_player_at(id, name, None)
+    }
+
+    pub fn add_player_at(&mut self, id: impl Into<PlayerId>, name: &str, seat: Option<usize>) -> Result<&Player, String> {
+        let id = id.into();
+        if self.players.contains_key(&id) {
+            return Err(self.text_for(&id, Msg::AlreadyJoined { name: self.players[&id].username.clone() }))
+        }
+        if let Some(player) = self.finished.iter().chain(self.dropped.iter()).find(|player| player.id == id) {
+            return Err(self.text_for(&id, Msg::AlreadyPlayed { name: player.username.clone() }))
+        }
+        let name = self.validate_name(&id, name)?;
+        if self.is_over() {
+            return Err(self.text_for(&id, Msg::GameEnded))
+        }
+        if self.started && !self.rules.bool(RuleKey::AllowLateJoin) {
+            return Err(self.text_for(&id, Msg::AlreadyStarted))
+        }
+        let hand_size = self.late_join_hand();
+        self.spectators.remove(&id);
+        self.players.insert(id.clone(), Player::new(id.clone(), name.as_str()));
+        if self.host.is_none() {
+            self.host = Some(id.clone());
+        }
+        let index = match seat {
+            Some(seat) if self.started => seat.clamp(1, self.queue.len()),
+            Some(seat) => seat.min(self.queue.len()),
+            None => self.queue.len(),
         };
-        let id = player.id;
-        self.players.insert(player.id, player);
-        return &self.players[&id]
+        self.queue.insert(index, id.clone());
+        if self.started {
+            self.deal(&id, hand_size);
+            self.announce(&id, Msg::JoinedWithCards { name: name.clone(), count: hand_size });
+            let locale = self.locale_of(&id);
+            let player = self.players.get_mut(&id).unwrap();
+            let hand = player.hand_message(locale);
+            player.send_message(hand);
+        }
+        else {
+            self.announce(&id, Msg::Joined { name: name.clone() });
+        }
+        self.record(&id, Action::Join { name, seat });
+        Ok(&self.players[&id])
     }
-    fn shuffle_deck(&mut self) {
-        self.deck.shuffle(&mut thread_rng())
+
+    pub fn add_bot(&mut self, by: &PlayerId, preset: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        let presets = BOT_PRESETS.iter().map(|preset| preset.name).collect::<Vec<&str>>().join(", ");
+        let preset = BotPreset::find(preset).ok_or_else(|| self.text_for(by, Msg::BotPresetNotFound { preset: preset.to_string(), presets }))?;
+        let taken = |id: &PlayerId| self.players.contains_key(id) || self.finished.iter().chain(self.dropped.iter()).any(|player| player.id == *id);
+        let seat = (1..).find(|n| !taken(&PlayerId::from(format!("bot-{}", n)))).unwrap();
+        let id = PlayerId::from(format!("bot-{}", seat));
+        let name = format!("{} bot {}", preset.name, seat);
+        self.add_player(id.clone(), name.as_str())?;
+        self.bots.insert(id, Bot::new(preset, self.log.seed.wrapping_add(seat)));
+        Ok(self.text_for(by, Msg::BotJoined { name, preset: preset.name.to_string() }))
     }
 
-    pub fn remove_player(&mut self, player_id: i32) {
-        let player = self.players.get_mut(&player_id);
-        if let Some(player) = player {
-            self.dropped.push(player.clone());
-            if self.queue[0].id == player_id {
-                self.next();
-            };
-            self.players.retain(|f_player, _| *f_player != player_id);
-            self.queue.retain(|f_player| f_player.id != player_id);
+    pub fn is_bot(&self, id: &PlayerId) -> bool {
+        self.bots.contains_key(id)
+    }
+
+    fn late_join_hand(&mut self) -> i32 {
+        let initial = self.rules.int(RuleKey::InitialCards);
+        let sizes = self.queue.iter().map(|id| self.players[id].hand.len() as i32).collect::<Vec<i32>>();
+        if sizes.is_empty() {
+            return initial
         }
-        else {
-            panic!("Player with id {} not found",player_id)
+        match self.rules.choice::<LateJoinHand>() {
+            LateJoinHand::Initial => initial,
+            LateJoinHand::Average => (sizes.iter().sum::<i32>() as f64 / sizes.len() as f64).round() as i32,
+            LateJoinHand::Max => *sizes.iter().max().unwrap(),
         }
     }
-    
-    pub fn set_rule(&mut self, rule: &str, value: i32) -> String {
-        let found_rule = self.get_rule(rule);
-        if let Some(rule) = found_rule {
-            if rule.rtype == "integer" {
-                if value > rule.max || value < rule.min {
-                    return format!("Value {} is out of bounds for this rule", value)
-                }
-                self.rules[rule.idx as usize].value = value;
+
+    fn announce(&mut self, except: &PlayerId, msg: Msg) {
+        for (id, player) in self.players.iter_mut() {
+            if id != except {
+                let locale = self.locales.get(id).copied().unwrap_or(self.locale);
+                player.send_message(msg.text(locale));
+            }
+        }
+        self.feed.push((self.turns, msg.text(self.locale)));
+    }
+
+    fn text(&self, msg: Msg) -> String {
+        msg.text(self.locale)
+    }
+
+    pub(crate) fn text_for(&self, id: &PlayerId, msg: Msg) -> String {
+        msg.text(self.locale_of(id))
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    pub fn locale_of(&self, id: &PlayerId) -> Locale {
+        self.locales.get(id).copied().unwrap_or(self.locale)
+    }
+
+    pub fn set_locale(&mut self, by: &PlayerId, code: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        let locale = Locale::find(code).ok_or_else(|| self.text_for(by, Msg::LocaleNotFound { locale: code.to_string() }))?;
+        self.locale = locale;
+        Ok(self.text_for(by, Msg::LocaleSet { locale: locale.to_string() }))
+    }
+
+    pub fn set_player_locale(&mut self, id: &PlayerId, code: &str) -> Result<String,String> {
+        if !self.players.contains_key(id) && !self.spectators.contains_key(id) {
+            return Err(self.text_for(id, Msg::PlayerNotFound { id: id.to_string() }))
+        }
+        let locale = Locale::find(code).ok_or_else(|| self.text_for(id, Msg::LocaleNotFound { locale: code.to_string() }))?;
+        self.locales.insert(id.clone(), locale);
+        Ok(self.text_for(id, Msg::LocaleSet { locale: locale.to_string() }))
+    }
+
+    pub(crate) fn player_name(&self, id: &PlayerId) -> String {
+        self.players.get(id).or_else(|| self.dropped.iter().find(|player| player.id == *id))
+            .map(|player| player.username.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn validate_name(&self, id: &PlayerId, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(self.text_for(id, Msg::NameEmpty))
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(self.text_for(id, Msg::NameTooLong { max: MAX_NAME_LENGTH }))
+        }
+        if name.chars().any(|c| c.is_control()) {
+            return Err(self.text_for(id, Msg::NameControl))
+        }
+        Ok(name.to_string())
+    }
+
+    fn shuffle_deck(&mut self) {
+        self.deck.shuffle(&mut self.rng)
+    }
+
+    pub fn remove_player(&mut self, player_id: &PlayerId) -> Result<String,String> {
+        let out = self.drop_player(player_id)?;
+        self.record(player_id, Action::Leave);
+        Ok(out)
+    }
+
+    fn drop_player(&mut self, player_id: &PlayerId) -> Result<String,String> {
+        let player = self.players.remove(player_id);
+        if let Some(mut player) = player {
+            let was_current = self.queue.first() == Some(player_id);
+            self.queue.retain(|f_player| f_player != player_id);
+            self.kick_votes.remove(player_id);
+            self.bots.remove(player_id);
+            self.stand_ins.remove(player_id);
+            self.banks.remove(player_id);
+            self.timeouts.remove(player_id);
+            for voters in self.kick_votes.values_mut().chain(self.rule_votes.values_mut()) {
+                voters.remove(player_id);
             }
-            else if rule.rtype == "boolean" {
-                if value != 1 && value != 0 {
-                    return format!("Value for rule {} must be 0 or 1", rule.name)
+            if self.host.as_ref() == Some(player_id) {
+                self.transfer_host();
+            }
+            if !self.started || player.finished || self.is_over() {
+                let msg = Msg::Left { name: player.username.clone() };
+                self.announce(player_id, msg.clone());
+                return Ok(self.text_for(player_id, msg))
+            }
+            if was_current {
+                self.called_out = false;
+                if self.queue.len() >= 2 {
+                    self.emit(GameEvent::TurnStart(self.queue[0].clone()));
                 }
-                self.rules[rule.idx as usize].value = value;
             }
-            return "Rule changed".to_string()
+            let mut cards = std::mem::take(&mut player.hand);
+            let returned = cards.len();
+            if self.rules.choice::<LeaverCards>() == LeaverCards::Discard {
+                cards.append(&mut self.discard);
+                self.discard = cards;
+            }
+            else {
+                self.deck.append(&mut cards);
+                self.shuffle_deck();
+            }
+            self.dropped.push(player.clone());
+            let msg = Msg::LeftReturned { name: player.username.clone(), count: returned };
+            self.announce(player_id, msg.clone());
+            let mut out = self.text_for(player_id, msg);
+            if self.queue.len() < 2 {
+                out.push_str("\n\n");
+                out.push_str(self.end_game().as_str());
+            }
+            Ok(out)
         }
         else {
-            panic!("Rule {} not found", rule)
+            Err(self.text_for(player_id, Msg::PlayerNotFound { id: player_id.to_string() }))
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.started && self.queue.is_empty()
+    }
+
+    fn end_game(&mut self) -> String {
+        self.time_ended = Some(self.minutes());
+        let mut left = std::mem::take(&mut self.queue);
+        left.sort_by_key(|id| hand_value(&self.players[id]));
+        for id in left {
+            let mut player = self.players[&id].clone();
+            player.finished = true;
+            self.finished.push(player);
+            let rank = self.ranks().last().map(|(_, rank)| *rank).unwrap();
+            self.emit(GameEvent::PlayerFinished(id, rank));
+        }
+        self.emit(GameEvent::GameEnd);
+        self.scoreboard()
+    }
+
+    /// Everyone who finished with their rank. Players still holding cards when the game ended are ranked
+    /// by how many cards they held, then by what those cards are worth, and share a rank when both match.
+    pub fn ranks(&self) -> Vec<(PlayerId, usize)> {
+        let mut ranks: Vec<(PlayerId, usize)> = Vec::new();
+        for (idx, player) in self.finished.iter().enumerate() {
+            let tied = idx > 0 && !player.hand.is_empty() && hand_value(&self.finished[idx - 1]) == hand_value(player);
+            let rank = if tied { ranks[idx - 1].1 } else { idx + 1 };
+            ranks.push((player.id.clone(), rank));
+        }
+        ranks
+    }
+
+    pub fn get_player(&self, player_id: &PlayerId) -> Option<&Player> {
+        self.players.get(player_id)
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = &Player> {
+        self.queue.iter().map(|id| &self.players[id])
+    }
+
+    pub fn set_rule(&mut self, by: &PlayerId, rule: &str, value: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
+        }
+        let rule = self.get_rule(rule).ok_or_else(|| self.text_for(by, Msg::RuleNotFound { rule: rule.to_string() }))?;
+        let value = rule.parse(value).map_err(|err| self.text_for(by, err))?;
+        self.rules.set(rule.key, value).map_err(|err| self.text_for(by, err))?;
+        self.record(by, Action::SetRule { rule: rule.id.to_string(), value: value.to_string() });
+        Ok(self.text_for(by, Msg::RuleSet { rule: rule.name.to_string(), value: value.to_string() }))
+    }
+
+    pub fn apply_preset(&mut self, by: &PlayerId, preset: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
+        }
+        let preset = Preset::find(preset).ok_or_else(|| self.text_for(by, Msg::PresetNotFound { preset: preset.to_string() }))?;
+        self.rules.apply_preset(preset);
+        self.record(by, Action::ApplyPreset { preset: preset.name.to_string() });
+        Ok(self.text_for(by, Msg::PresetApplied { preset: preset.name.to_string() }))
+    }
+
+    pub fn export_rules(&self, format: RuleFormat) -> String {
+        self.rules.export(format)
+    }
+
+    pub fn import_rules(&mut self, by: &PlayerId, input: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
+        }
+        self.rules = self.rules.import(input, self.locale_of(by)).map_err(|errors| self.text_for(by, Msg::ImportFailed { errors: errors.join("\n") }))?;
+        self.record(by, Action::ImportRules { input: input.to_string() });
+        Ok(self.text_for(by, Msg::RulesImported))
+    }
+
+    pub fn add_alias(&mut self, by: &PlayerId, alias: &str, card: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
         }
+        let (alias, card) = self.rules.aliases_mut().add(alias, card).map_err(|msg| self.text_for(by, msg))?;
+        self.record(by, Action::AddAlias { alias: alias.clone(), card: card.clone() });
+        Ok(self.text_for(by, Msg::AliasAdded { alias, card }))
     }
 
-    pub fn show_rule(&mut self, rule: &str) -> String {
+    pub fn remove_alias(&mut self, by: &PlayerId, alias: &str) -> Result<String,String> {
+        self.check_host(by)?;
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
+        }
+        let alias = alias.trim().to_lowercase();
+        if self.rules.aliases_mut().remove(alias.as_str()).is_none() {
+            return Err(self.text_for(by, Msg::AliasNotFound { alias }))
+        }
+        self.record(by, Action::RemoveAlias { alias: alias.clone() });
+        Ok(self.text_for(by, Msg::AliasRemoved { alias }))
+    }
+
+    pub fn show_aliases(&self) -> String {
+        let rows = self.rules.aliases().iter()
+            .map(|(alias, card)| self.text(Msg::AliasRow { alias: alias.to_string(), card: card.to_string() }))
+            .collect::<Vec<String>>();
+        if rows.is_empty() {
+            self.text(Msg::NoAliases)
+        }
+        else {
+            rows.join("\n")
+        }
+    }
+
+    pub fn show_presets(&self) -> String {
+        PRESETS.iter().map(|preset| format!("*{}*\n{}", preset.name, preset.desc)).collect::<Vec<String>>().join("\n\n")
+    }
+
+    pub fn show_bot_presets(&self) -> String {
+        BOT_PRESETS.iter().map(|preset| format!("*{}*\n{}", preset.name, preset.desc)).collect::<Vec<String>>().join("\n\n")
+    }
+
+    pub fn show_rule(&self, rule: &str) -> String {
         let found_rule = self.get_rule(rule);
         if let Some(rule) = found_rule {
-            format!("*{}*\nType: {}\nValue: {}\n\n{}", rule.name,rule.rtype,rule.value,rule.desc)
+            self.rule_info(rule, self.rules.get(rule.key))
         }
         else {
-            return String::from("")
+            String::from("")
         }
     }
-    
-    pub fn show_all_rules(&mut self) -> String {
-        let mut rules = String::new();
-        for rule in UnoGame::generate_rules() {
-            rules.push_str(&format!("*{}*\nType: {}\nValue: {}\n{}\n\n", rule.name,rule.rtype,rule.value,rule.desc))
-        }
-        rules
+
+    pub fn show_all_rules(&self) -> String {
+        self.rules.iter().map(|(rule, value)| self.rule_info(rule, value)).collect::<Vec<String>>().join("\n\n")
+    }
+
+    fn rule_info(&self, rule: &Rule, value: RuleValue) -> String {
+        self.text(Msg::RuleInfo {
+            name: rule.name.to_string(),
+            id: rule.id.to_string(),
+            kind: rule.kind.to_string(),
+            value: value.to_string(),
+            desc: rule.desc.to_string(),
+        })
+    }
+
+    pub fn get_rule(&self, get_rule: &str) -> Option<&'static Rule> {
+        Rule::find(get_rule)
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn get_curr_player(&self) -> &Player {
+        &self.players[&self.queue[0]]
+    }
+
+    pub fn get_curr_card(&self) -> &Card {
+        self.discard.last().unwrap()
+    }
+
+    pub fn discard_pile(&self) -> &[Card] {
+        &self.discard
     }
 
-    pub fn get_rule(&mut self , get_rule: &str) -> Option<&Rule>{
-        self.rules.iter().find(|rule| rule.name.to_lowercase() == get_rule.to_lowercase())
+    pub fn deck_size(&self) -> usize {
+        self.deck.len()
     }
 
-    pub fn get_curr_player(&mut self) -> &Player {
-        &self.queue[0]
+    pub fn cards_drawn(&self) -> i32 {
+        self.drawn
     }
 
-    pub fn get_curr_card(&mut self) -> &Card {
-        &self.discard.last().unwrap()
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    pub fn standings(&self) -> &[Player] {
+        &self.finished
+    }
+
+    pub fn dropped(&self) -> &[Player] {
+        &self.dropped
     }
 
     fn next(&mut self) -> &Player {
         if self.queue.is_empty() {
-            panic!("Game has ended!")   
+            panic!("Game has ended!")
         }
-        let player = self.queue[0].clone();
-        self.queue.remove(0usize);
+        let player = self.queue.remove(0usize);
         self.queue.push(player);
-        self.queue.retain(|player| !player.finished);
-        &self.queue[0]
+        let players = &self.players;
+        self.queue.retain(|player| !players[player].finished);
+        self.emit(GameEvent::TurnStart(self.queue[0].clone()));
+        &self.players[&self.queue[0]]
     }
 
-    pub fn notify_player(&mut self, id: i32, msg: &str) -> String{
-        let fplayer = self.players.get_mut(&id);
+    pub fn notify_player(&mut self, id: &PlayerId, msg: &str) -> String{
+        let fplayer = self.players.get_mut(id);
         if let Some(player) = fplayer {
             player.messages.push(msg.to_string());
             msg.to_string()
@@ -240,298 +828,952 @@ impl UnoGame {
             panic!("Player {} not found",id)
         }
     }
+}
+
+fn hand_value(player: &Player) -> (usize, i32) {
+    (player.hand.len(), player.hand.iter().map(|card| card.points()).sum())
+}
+
+//Lobby
+impl UnoGame {
+    pub fn host(&self) -> Option<&PlayerId> {
+        self.host.as_ref()
+    }
+
+    pub fn is_host(&self, id: &PlayerId) -> bool {
+        self.host.as_ref() == Some(id)
+    }
+
+    fn check_host(&self, by: &PlayerId) -> Result<(), String> {
+        if self.is_host(by) {
+            Ok(())
+        }
+        else {
+            Err(self.text_for(by, Msg::OnlyHost))
+        }
+    }
+
+    fn transfer_host(&mut self) {
+        self.host = self.queue.first().or_else(|| self.players.keys().min()).cloned();
+        if let Some(host) = self.host.clone() {
+            self.announce(&host, Msg::NewHost { name: self.players[&host].username.clone() });
+            let msg = self.text_for(&host, Msg::YouAreHost);
+            self.notify_player(&host, msg.as_str());
+        }
+    }
+
+    pub fn set_host(&mut self, by: &PlayerId, new_host: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        if let Some(player) = self.players.get(new_host) {
+            let msg = self.text_for(by, Msg::NewHost { name: player.username.clone() });
+            self.host = Some(new_host.clone());
+            self.record(by, Action::SetHost { player: new_host.clone() });
+            Ok(msg)
+        }
+        else {
+            Err(self.text_for(by, Msg::PlayerNotFound { id: new_host.to_string() }))
+        }
+    }
+
+    pub fn lock_rules(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        self.rules_locked = true;
+        self.record(by, Action::LockRules);
+        Ok(self.text_for(by, Msg::RulesLockedNow))
+    }
+
+    pub fn unlock_rules(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        self.rules_locked = false;
+        self.record(by, Action::UnlockRules);
+        Ok(self.text_for(by, Msg::RulesUnlocked))
+    }
+
+    pub fn kick(&mut self, by: &PlayerId, player_id: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        if by == player_id {
+            return Err(self.text_for(by, Msg::CannotKickSelf))
+        }
+        let out = self.drop_player(player_id)?;
+        self.record(by, Action::Kick { player: player_id.clone() });
+        Ok(out)
+    }
 
+    pub fn end(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        if !self.started || self.is_over() {
+            return Err(self.text_for(by, Msg::NoRunningGame))
+        }
+        let out = self.end_game();
+        self.record(by, Action::End);
+        Ok(out)
+    }
 
+    pub fn undo(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        if !self.rules.bool(RuleKey::Undo) {
+            return Err(self.text_for(by, Msg::UndoDisabled))
+        }
+        let entry = match self.log.entries.last() {
+            Some(entry) if entry.action.is_move() => entry.clone(),
+            _ => return Err(self.text_for(by, Msg::NothingToUndo))
+        };
+        if let Some(undone) = self.redo.last() {
+            if undone.by != entry.by {
+                return Err(self.text_for(by, Msg::UndoBlocked))
+            }
+        }
+        let mut log = self.log.clone();
+        log.entries.pop();
+        let rebuilt = UnoGame::replay(&log)?;
+        let mut redo = std::mem::take(&mut self.redo);
+        redo.push(entry.clone());
+        let observers = std::mem::take(&mut self.observers);
+        let bots = std::mem::take(&mut self.bots);
+        let stand_ins = std::mem::take(&mut self.stand_ins);
+        let clock = self.clock.clone();
+        let mut spectators = std::mem::take(&mut self.spectators);
+        let locales = std::mem::take(&mut self.locales);
+        let locale = self.locale;
+        *self = rebuilt;
+        self.redo = redo;
+        self.observers = observers;
+        self.bots = bots;
+        self.stand_ins = stand_ins;
+        self.clock = clock;
+        self.locales = locales;
+        self.locale = locale;
+        for spectator in spectators.values_mut() {
+            spectator.seen = spectator.seen.min(self.feed.len());
+        }
+        self.spectators = spectators;
+        Ok(self.text_for(by, Msg::Undid { action: entry.action.to_string(), name: self.player_name(&entry.by) }))
+    }
+
+    pub fn redo(&mut self, by: &PlayerId) -> Result<String,String> {
+        self.check_host(by)?;
+        let entry = self.redo.pop().ok_or_else(|| self.text_for(by, Msg::NothingToRedo))?;
+        let redo = std::mem::take(&mut self.redo);
+        self.set_replay_clock(Some(entry.at));
+        let res = self.apply(&entry);
+        self.set_replay_clock(None);
+        match res {
+            Ok(out) => {
+                self.redo = redo;
+                Ok(out)
+            }
+            Err(err) => Err(err)
+        }
+    }
+
+    fn votes_needed(&self, threshold: i32, exclude: Option<&PlayerId>) -> usize {
+        let voters = self.queue.iter().filter(|id| Some(*id) != exclude).count();
+        ((voters as i32 * threshold + 99) / 100).max(1) as usize
+    }
+
+    pub fn vote_kick(&mut self, by: &PlayerId, player_id: &PlayerId) -> Result<String,String> {
+        let threshold = self.rules.int(RuleKey::VoteKickThreshold);
+        if threshold == 0 {
+            return Err(self.text_for(by, Msg::VoteKickDisabled))
+        }
+        if !self.queue.contains(by) {
+            return Err(self.text_for(by, Msg::OnlyPlayersVote))
+        }
+        if by == player_id {
+            return Err(self.text_for(by, Msg::CannotVoteSelf))
+        }
+        if !self.queue.contains(player_id) {
+            return Err(self.text_for(by, Msg::PlayerNotFound { id: player_id.to_string() }))
+        }
+        let needed = self.votes_needed(threshold, Some(player_id));
+        let voters = self.kick_votes.entry(player_id.clone()).or_default();
+        voters.insert(by.clone());
+        let votes = voters.len();
+        if votes >= needed {
+            self.kick_votes.remove(player_id);
+            let out = self.drop_player(player_id)?;
+            self.record(by, Action::VoteKick { player: player_id.clone() });
+            return Ok(self.text_for(by, Msg::VotePassed { result: out }))
+        }
+        self.record(by, Action::VoteKick { player: player_id.clone() });
+        Ok(self.text_for(by, Msg::KickVotes { votes, needed, name: self.players[player_id].username.clone() }))
+    }
 
-    fn generate_rules() -> [Rule; 10] {
-        [
-            Rule{
-                idx: 0,
-                desc: "The number of decks to use.".to_string(),
-                value: 1,
-                name: "Decks".to_string(),
-                rtype: "integer".to_string(),
-                max: 8,
-                min: 1,
-            },
-            Rule{
-                idx: 1,
-                desc: "How many cards to pick up at the beginning.".to_string(),
-                value: 7,
-                name: "Initial Cards".to_string(),
-                rtype: "integer".to_string(),
-                min: 1,
-                max: 5000,
-            },
-            Rule {
-                idx: 2,
-                desc: "Whether pickup cards (+2, +4) should also skip the next person's turn.".to_string(),
-                value: 1,
-                name: "Draws Skip".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            },
-            Rule {
-                idx: 3,
-                desc: "Whether reverse cards skip turns when there's only two players left.".to_string(),
-                value: 1,
-                name: "Reverses Skip".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            },
-            Rule {
-                idx: 4,
-                desc: "Whether someone must play a card if they are able to.".to_string(),
-                value: 0,
-                name: "Must Play".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            },
-            Rule {
-                idx: 5,
-                desc: "Gives the ability to call someone out for not saying uno!".to_string(),
-                value: 1,
-                name: "Callouts".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            },
-            Rule {
-                idx: 6,
-                desc: "The number of cards to give someone when called out.".to_string(),
-                value: 2,
-                name: "Callout Penalty".to_string(),
-                rtype: "integer".to_string(),
-                max: 1000,
-                min: 0,
-            },
-            Rule {
-                idx: 7,
-                desc: "The number of cards to give someone for falsely calling someone out.".to_string(),
-                value: 2,
-                name: "False Callout Penalty".to_string(),
-                rtype: "integer".to_string(),
-                max: 1000,
-                min: 0,
-            },
-            Rule {
-                idx: 8,
-                desc: "Automatically plays a card after drawing, if possible. If a wild card is drawn, will give a prompt for color.".to_string(),
-                value: 0,
-                name: "Automatically Play After Draw".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            },
-            Rule {
-                idx: 9,
-                desc: "Automatically proceeds to the next turn after drawing, meaning that you cannot play drawn cards (without DRAW_AUTOPLAY).".to_string(),
-                value: 1,
-                name: "Automatically Pass Turns (WIP)".to_string(),
-                rtype: "boolean".to_string(),
-                min: 0,
-                max: 0,
-            }
-        ]
+    pub fn vote_rule(&mut self, by: &PlayerId, rule: &str, value: &str) -> Result<String,String> {
+        let threshold = self.rules.int(RuleKey::VoteRuleThreshold);
+        if threshold == 0 {
+            return Err(self.text_for(by, Msg::VoteRuleDisabled))
+        }
+        if self.rules_locked {
+            return Err(self.text_for(by, Msg::RulesLocked))
+        }
+        if !self.queue.contains(by) {
+            return Err(self.text_for(by, Msg::OnlyPlayersVote))
+        }
+        let rule = self.get_rule(rule).ok_or_else(|| self.text_for(by, Msg::RuleNotFound { rule: rule.to_string() }))?;
+        let value = rule.parse(value).map_err(|err| self.text_for(by, err))?;
+        let needed = self.votes_needed(threshold, None);
+        self.record(by, Action::VoteRule { rule: rule.id.to_string(), value: value.to_string() });
+        let voters = self.rule_votes.entry((rule.key, value)).or_default();
+        voters.insert(by.clone());
+        let votes = voters.len();
+        if votes >= needed {
+            self.rule_votes.retain(|(key, _), _| *key != rule.key);
+            self.rules.set(rule.key, value).map_err(|err| self.text_for(by, err))?;
+            let result = self.text_for(by, Msg::RuleSet { rule: rule.name.to_string(), value: value.to_string() });
+            return Ok(self.text_for(by, Msg::VotePassed { result }))
+        }
+        Ok(self.text_for(by, Msg::RuleVotes { votes, needed, rule: rule.name.to_string(), value: value.to_string() }))
     }
 }
 
 //Commands
 impl UnoGame {
     pub fn play(&mut self, card: String) -> Result<String,String> {
+        if !self.started {
+            return Err(self.text(Msg::NotStarted))
+        }
+        let by = self.queue.first().cloned().ok_or_else(|| self.text(Msg::GameEnded))?;
+        let (out, mut played) = self.play_cards(&[card.as_str()])?;
+        let played = played.remove(0);
+        self.record(&by, Action::Play { card: played.id, color: played.color });
+        Ok(out)
+    }
+
+    pub fn play_many(&mut self, cards: &[&str]) -> Result<String,String> {
+        if !self.started {
+            return Err(self.text(Msg::NotStarted))
+        }
+        let by = self.queue.first().cloned().ok_or_else(|| self.text(Msg::GameEnded))?;
+        if cards.is_empty() {
+            return Err(self.text_for(&by, Msg::EmptyCard))
+        }
+        if cards.len() > 1 && !self.rules.bool(RuleKey::MultiPlay) {
+            return Err(self.text_for(&by, Msg::MultiPlayDisabled))
+        }
+        let (out, played) = self.play_cards(cards)?;
+        let action = match played.as_slice() {
+            [card] => Action::Play { card: card.id.clone(), color: card.color.clone() },
+            _ => Action::PlayMany { card: played[0].id.clone(), colors: played.iter().map(|card| card.color.clone()).collect() },
+        };
+        self.record(&by, action);
+        Ok(out)
+    }
+
+    fn play_cards(&mut self, inputs: &[&str]) -> Result<(String, Vec<Card>),String> {
         if self.queue.is_empty() {
-            Err("Game has ended!".to_string())
+            return Err(self.text(Msg::GameEnded))
         }
-        else {
-            let rev_skip = self.get_rule("Reverses Skip").unwrap().value;
-            let draw_skip = self.get_rule("Draws Skip").unwrap().value;
-            let player = &mut self.queue[0];
-
-            let found_card = player.get_card(&(card.split_whitespace().collect()));
-
-            if let Some(found_card) = found_card {
-
-                let card = player.hand.iter().find(|cards| cards.num == found_card).unwrap().clone();
-                let curr_card = self.discard.last().unwrap();
-
-                if curr_card.wild || curr_card.color.is_empty() || curr_card.id == card.id || curr_card.color == card.color {
-                    self.called_out = false;
-                    self.discard.push(card.clone());
-                    player.hand.retain(|c_num| c_num.num != found_card);
-                    player.sort_hand();
-
-                    let mut prefix = String::new();
-                    let mut extra = String::new();
-
-                    if player.hand.len() == 0 {
-                        player.finished = true;
-                        self.finished.push(player.clone());
-                        prefix.push_str(format!("{} has no more cards. They finished in rank *{}*!\n\n", player.username, self.finished.len()).as_str());
-                        
-                        if self.queue.len() == 2 {
-                            prefix.push_str(self.scoreboard().as_str());
-                            self.finished.push(self.queue[1].clone());
-                            self.queue = Vec::new();
-                            return Ok(prefix)
-                        }
+        let rev_skip = self.rules.bool(RuleKey::ReversesSkip);
+        let draw_skip = self.rules.bool(RuleKey::DrawsSkip);
+        let locale = self.locale_of(&self.queue[0]);
+
+        let mut pool = self.players[&self.queue[0]].clone();
+        let mut cards: Vec<Card> = Vec::new();
+        let mut color = None;
+        for input in inputs {
+            let spec = card_input::parse(input, self.rules.aliases()).map_err(|err| err.msg().text(locale))?;
+            let found_card = pool.find_card(&spec, locale)?;
+            let card = pool.hand.iter().find(|cards| cards.num == found_card).unwrap().clone();
+            pool.hand.retain(|c_num| c_num.num != found_card);
+            if let Some(first) = cards.first() {
+                if first.id != card.id {
+                    return Err(Msg::MultiPlaySameFace { card: first.text(locale) }.text(locale))
+                }
+            }
+            color = spec.color;
+            cards.push(card);
+        }
+        if cards[0].wild {
+            let Some(color) = color else {
+                return Err(Msg::ChooseColor { card: cards[0].id.clone() }.text(locale))
+            };
+            for card in cards.iter_mut() {
+                card.color = color.clone();
+            }
+        }
+        let curr_card = self.discard.last().unwrap();
+        if !cards[0].can_play_on(curr_card) {
+            return Err(Msg::CannotPlay { card: curr_card.text(locale) }.text(locale))
+        }
+
+        self.called_out = false;
+        let player = self.players.get_mut(&self.queue[0]).unwrap();
+        let id = player.id.clone();
+        for card in &cards {
+            self.discard.push(card.clone());
+            player.hand.retain(|c_num| c_num.num != card.num);
+            self.events.push(GameEvent::CardPlayed(id.clone(), card.clone()));
+        }
+        player.sort_hand();
+
+        let mut prefix = String::new();
+        let mut extra = String::new();
+
+        if player.hand.is_empty() {
+            player.finished = true;
+            self.finished.push(player.clone());
+            self.events.push(GameEvent::PlayerFinished(id, self.finished.len()));
+            prefix.push_str(Msg::FinishedRank { name: player.username.clone(), rank: self.finished.len() }.text(locale).as_str());
+            prefix.push_str("\n\n");
+
+            if self.queue.len() == 2 {
+                self.queue.remove(0);
+                prefix.push_str(self.end_game().as_str());
+                return Ok((prefix, cards))
+            }
+        }
+
+        let card = cards.last().unwrap().clone();
+        let count = cards.len() as i32;
+        match card.id.as_str() {
+            "REVERSE" => {
+                for _ in 0..count {
+                    if self.queue.len() > 2 {
+                        self.queue.reverse();
+                        let ins = self.queue.pop().unwrap();
+                        self.queue.insert(0, ins);
                     }
-                    
-                    match card.id.as_str() {
-                        "REVERSE" => {
-                            if self.queue.len() > 2 {
-                                self.queue.reverse();
-                                let ins = self.queue.pop().unwrap();
-                                self.queue.insert(0, ins);
-                                extra.push_str("Turns are now in reverse order!");
-                            }
-                            else if rev_skip == 1 {
-                                self.queue.reverse();
-                                extra.push_str(format!("{}, skip a turn!", self.queue[0].username.clone()).as_str());
-                            };
-                        }
-                        "SKIP" => {
-                            let ins = self.queue.remove(0);
-                            self.queue.push(ins);
-                            extra.push_str(format!("{}, skip a turn!", self.queue[0].username.clone()).as_str());
-                        }
-                        "+2" => {
-                            let mut amount = 0;
-                            for i in (self.discard.len() - 1) ..=0 {
-                                if self.discard[i].id == "+2" {
-                                    amount += 2;
-                                }
-                                else {
-                                    break;
-                                }
-                            }
-                            self.deal(self.queue[1].id, amount);
-                            extra.push_str(format!("{} picks up {}!",self.queue[1].username.clone(), amount ).as_str());
-                            if draw_skip == 1 {
-                                extra.push_str("Also, skip a turn!");
-                                let ins = self.queue.remove(0);
-                                self.queue.push(ins);
-                            }
-                        }
-                        "WILD" => {
-                            extra.push_str(format!("The color is now {}", card.color).as_str());
-                        }
-                        "WILD+4" => {
-                            self.deal(self.queue[1].id, 4);
-                            extra.push_str(format!("{} picks up! The current color is now {}", self.queue[1].username.clone(), card.color).as_str());
-                            if draw_skip == 1 {
-                                extra.push_str("Also, skip a turn!");
-                                let ins = self.queue.remove(0);
-                                self.queue.push(ins);
-                            }
-                        }
-                        _ => { 
-                            
+                    else if rev_skip {
+                        self.queue.reverse();
+                        if !extra.is_empty() {
+                            extra.push(' ');
                         }
+                        extra.push_str(Msg::SkipTurn { name: self.players[&self.queue[0]].username.clone() }.text(locale).as_str());
                     };
-                    self.next();
-                    Ok(prefix)
+                }
+                if self.queue.len() > 2 && count % 2 == 1 {
+                    extra.push_str(Msg::Reversed.text(locale).as_str());
+                }
+            }
+            "SKIP" => {
+                for _ in 0..count {
+                    let ins = self.queue.remove(0);
+                    self.queue.push(ins);
+                    if !extra.is_empty() {
+                        extra.push(' ');
+                    }
+                    extra.push_str(Msg::SkipTurn { name: self.players[&self.queue[0]].username.clone() }.text(locale).as_str());
+                }
+            }
+            "+2" => {
+                let amount = 2 * count;
+                let target = self.queue[1].clone();
+                self.deal(&target, amount);
+                self.emit(GameEvent::Draw(target.clone(), amount, true));
+                extra.push_str(Msg::PicksUp { name: self.players[&target].username.clone(), count: amount }.text(locale).as_str());
+                if draw_skip {
+                    extra.push(' ');
+                    extra.push_str(Msg::AlsoSkip.text(locale).as_str());
+                    let ins = self.queue.remove(0);
+                    self.queue.push(ins);
+                }
+            }
+            "WILD" => {
+                extra.push_str(Msg::ColorNow { color: color_text(&card.color, locale) }.text(locale).as_str());
+            }
+            "WILD+4" => {
+                let amount = 4 * count;
+                let target = self.queue[1].clone();
+                self.deal(&target, amount);
+                self.emit(GameEvent::Draw(target.clone(), amount, true));
+                if count > 1 {
+                    extra.push_str(Msg::PicksUp { name: self.players[&target].username.clone(), count: amount }.text(locale).as_str());
+                    extra.push(' ');
+                    extra.push_str(Msg::ColorNow { color: color_text(&card.color, locale) }.text(locale).as_str());
                 }
                 else {
-                    Err(format!("You cannot play this card here. Last played card was {} {}",curr_card.id, curr_card.color))
+                    extra.push_str(Msg::PicksUpColor { name: self.players[&target].username.clone(), color: color_text(&card.color, locale) }.text(locale).as_str());
+                }
+                if draw_skip {
+                    extra.push(' ');
+                    extra.push_str(Msg::AlsoSkip.text(locale).as_str());
+                    let ins = self.queue.remove(0);
+                    self.queue.push(ins);
                 }
             }
-            else {
-                Err(format!("Card {} not found in hand, its currently {}'s turn", card, player.username))
+            _ => {
+
             }
-        }
+        };
+        self.next();
+        prefix.push_str(extra.as_str());
+        Ok((prefix, cards))
     }
-    
+
     pub fn draw(&mut self) -> Result<String,String> {
-        let must_play = self.get_rule("Must Play").unwrap().value;
-        let draw_autoplay = self.get_rule("Automatically Play After Draw").unwrap().value;
-
-        let player = &mut self.queue[0];
-        if must_play == 1 {
-            for card in &player.hand {
-                let curr_card = self.discard.last().unwrap();
-                if curr_card.wild || curr_card.color.is_empty() || curr_card.id == card.id || curr_card.color == card.color {
-                    return Err("You must play a card if able.".to_string())
-                }
-            }
+        if !self.started {
+            return Err(self.text(Msg::NotStarted))
         }
-        let card_num = self.deal(self.queue[0].id, 1);
-        if draw_autoplay == 1 {
-            let card = self.queue[0].hand.iter().find(|cards| cards.num == card_num).unwrap().clone();
-            let curr_card = self.discard.last().unwrap();
-            if curr_card.wild || curr_card.color.is_empty() || curr_card.id == card.id || curr_card.color == card.color {
-                self.play(format!("{} {}", card.color, card.id)).expect("");
-            }
+        if self.queue.is_empty() {
+            return Err(self.text(Msg::GameEnded))
+        }
+        let must_play = self.rules.bool(RuleKey::MustPlay);
+        let draw_autoplay = self.rules.bool(RuleKey::DrawAutoplay);
+
+        let curr_id = self.queue[0].clone();
+        let player = &self.players[&curr_id];
+        if must_play && player.hand.iter().any(|card| card.can_play_on(self.discard.last().unwrap())) {
+            return Err(self.text_for(&curr_id, Msg::MustPlay))
+        }
+        let card_num = self.deal(&curr_id, 1);
+        self.emit(GameEvent::Draw(curr_id.clone(), 1, false));
+        let card = self.players[&curr_id].hand.iter().find(|cards| cards.num == card_num).unwrap().clone();
+        if draw_autoplay && !card.wild && card.can_play_on(self.discard.last().unwrap()) {
+            let (out, _) = self.play_cards(&[format!("{} {}", card.color, card.id).as_str()])?;
+            self.record(&curr_id, Action::Draw);
+            return Ok(self.text_for(&curr_id, Msg::DrewAndPlayed { card: card.text(self.locale_of(&curr_id)), result: out }))
         }
         self.next();
-        Ok(format!("{}", card_num))
+        self.record(&curr_id, Action::Draw);
+        Ok(self.text_for(&curr_id, Msg::Drew { card: card.text(self.locale_of(&curr_id)) }))
     }
 
-    pub fn callout(&mut self, call_player_id: i32) -> Result<String,String> {
-        let callouts = self.get_rule("Callouts").unwrap().value;
-        if callouts == 0 {
-           return Err("Callouts are not permitted in this game".to_string())
+    pub fn callout(&mut self, call_player_id: &PlayerId) -> Result<String,String> {
+        if !self.rules.bool(RuleKey::Callouts) {
+           return Err(self.text_for(call_player_id, Msg::CalloutsDisabled))
+        }
+
+        if !self.queue.contains(call_player_id) {
+            return Err(self.text_for(call_player_id, Msg::OnlyPlayersCallout))
         }
-        
+
         if self.called_out {
-            return Err("A callout was already performed in this turn!".to_string());
+            return Err(self.text_for(call_player_id, Msg::CalloutDone));
         }
-        
-        let callout_penalty = self.get_rule("Callout Penalty").unwrap().value;
-        let false_callout = self.get_rule("False Callout Penalty").unwrap().value;
-        
+
+        let callout_penalty = self.rules.int(RuleKey::CalloutPenalty);
+        let false_callout = self.rules.int(RuleKey::FalseCalloutPenalty);
+
         let mut called_out = false;
         let mut res = String::new();
-        let mut calls: Vec<i32> = Vec::new();
-        
-        for player in &self.queue {
+        let mut calls: Vec<PlayerId> = Vec::new();
+
+        for id in &self.queue {
+            let player = &self.players[id];
             if player.hand.len() == 1 && !player.called {
-                calls.push(player.id);
+                calls.push(player.id.clone());
                 called_out = true;
-                res.push_str(format!("{} you did not say UNO! Pick up {}",player.username.clone(),callout_penalty).as_str());
+                res.push_str(self.text_for(call_player_id, Msg::CaughtNoUno { name: player.username.clone(), count: callout_penalty }).as_str());
             }
         };
-        
-        for i in calls {
+
+        self.emit(GameEvent::Callout(call_player_id.clone(), calls.clone()));
+        for i in &calls {
             self.deal(i,callout_penalty);
+            self.emit(GameEvent::Draw(i.clone(), callout_penalty, true));
         }
-        return if !called_out {
+        self.called_out = true;
+        if !called_out {
             self.deal(call_player_id, false_callout);
-            self.called_out = true;
-            Ok(format!("There was no one to call out! Pick up {}", callout_penalty))
+            self.emit(GameEvent::Draw(call_player_id.clone(), false_callout, true));
+            self.record(call_player_id, Action::Callout { caught: calls });
+            Ok(self.text_for(call_player_id, Msg::FalseCallout { count: false_callout }))
         } else {
-            self.called_out = true;
+            self.record(call_player_id, Action::Callout { caught: calls });
             Ok(res)
         }
-        
+
     }
-    
-    pub fn uno(&mut self, call_player_id: i32) -> Result<String,String> {
-        let player: &mut Player = self.queue.iter_mut().find(|ply| ply.id == call_player_id).unwrap();
+
+    pub fn uno(&mut self, call_player_id: &PlayerId) -> Result<String,String> {
+        let locale = self.locale_of(call_player_id);
+        let player: &mut Player = self.players.get_mut(call_player_id).ok_or_else(|| Msg::PlayerNotFound { id: call_player_id.to_string() }.text(locale))?;
         if player.hand.len() == 1 {
             return if player.called {
-                Ok("You already said UNO!".to_string())
+                Ok(Msg::AlreadyUno.text(locale))
             } else {
                 player.called = true;
-                Ok("UNO!".to_string())
+                self.emit(GameEvent::Uno(call_player_id.clone()));
+                self.record(call_player_id, Action::Uno);
+                Ok(Msg::Uno.text(locale))
             }
         };
-        Err("You have more than 1 card!".to_string())
+        Err(Msg::MoreThanOneCard.text(locale))
+    }
+
+    pub fn run_bots(&mut self) -> Vec<String> {
+        let mut out = Vec::new();
+        while self.started && !self.is_over() {
+            let id = self.queue[0].clone();
+            let Some(mut bot) = self.bots.remove(&id) else {
+                break
+            };
+            let moved = self.bot_turn(&id, &mut bot, &mut out);
+            self.bots.insert(id, bot);
+            if !moved {
+                break
+            }
+        }
+        out
+    }
+
+    fn bot_turn(&mut self, id: &PlayerId, bot: &mut Bot, out: &mut Vec<String>) -> bool {
+        if bot.wants_callout(self, id) {
+            out.extend(self.callout(id).ok());
+        }
+        let Some(play) = bot.choose(self, id) else {
+            return false
+        };
+        match self.make_move(&play) {
+            Ok(res) => out.push(res),
+            Err(_) => return false,
+        }
+        if self.players.get(id).is_some_and(|player| player.hand.len() == 1) && bot.says_uno() {
+            out.extend(self.uno(id).ok());
+        }
+        true
     }
-    
-    pub fn table(&mut self) -> String {
+
+    pub fn time_left(&self) -> Option<u64> {
+        let turn = self.rules.int(RuleKey::TurnTimeout) as u64 * 1000;
+        let bank = self.rules.int(RuleKey::TimeBank) as u64 * 1000;
+        if !self.started || self.is_over() || (turn == 0 && bank == 0) {
+            return None
+        }
+        let bank = self.banks.get(&self.queue[0]).copied().unwrap_or(bank);
+        Some((turn + bank).saturating_sub(self.now().saturating_sub(self.turn_started)))
+    }
+
+    pub fn tick(&mut self) -> Vec<String> {
+        if self.time_left() != Some(0) {
+            return Vec::new()
+        }
+        let id = self.queue[0].clone();
+        if self.bots.contains_key(&id) {
+            return self.run_bots()
+        }
+        let mut out = Vec::new();
+        let name = self.player_name(&id);
+        let strikes = self.timeouts.get(&id).copied().unwrap_or(0) + 1;
+        match self.rules.choice::<TimeoutAction>() {
+            TimeoutAction::Bot => {
+                let preset = BotPreset::find(STAND_IN_PRESET).unwrap();
+                self.bots.insert(id.clone(), Bot::new(preset, self.log.seed.wrapping_add(self.turns as u64)));
+                self.stand_ins.insert(id.clone());
+                self.announce(&id, Msg::StandIn { name: name.clone() });
+                out.push(self.text_for(&id, Msg::StandIn { name }));
+                out.extend(self.run_bots());
+            }
+            TimeoutAction::Remove if strikes >= self.rules.int(RuleKey::AfkLimit) => {
+                out.push(self.text(Msg::RemovedAfk { name, count: strikes }));
+                out.extend(self.remove_afk());
+            }
+            TimeoutAction::Draw | TimeoutAction::Remove => out.extend(self.time_out()),
+        }
+        out
+    }
+
+    pub(crate) fn time_out(&mut self) -> Result<String,String> {
+        if !self.started {
+            return Err(self.text(Msg::NotStarted))
+        }
+        if self.queue.is_empty() {
+            return Err(self.text(Msg::GameEnded))
+        }
+        let id = self.queue[0].clone();
+        self.deal(&id, 1);
+        self.emit(GameEvent::Draw(id.clone(), 1, false));
+        self.next();
+        self.record(&id, Action::Timeout);
+        let msg = Msg::TimedOut { name: self.player_name(&id) };
+        self.announce(&id, msg.clone());
+        Ok(self.text_for(&id, msg))
+    }
+
+    pub(crate) fn remove_afk(&mut self) -> Result<String,String> {
+        if !self.started {
+            return Err(self.text(Msg::NotStarted))
+        }
+        let id = self.queue.first().cloned().ok_or_else(|| self.text(Msg::GameEnded))?;
+        let out = self.drop_player(&id)?;
+        self.record(&id, Action::RemoveAfk);
+        Ok(out)
+    }
+
+    pub fn return_to_seat(&mut self, id: &PlayerId) -> Result<String,String> {
+        if !self.stand_ins.remove(id) {
+            return Err(self.text_for(id, Msg::NotAway))
+        }
+        self.bots.remove(id);
+        let msg = Msg::BackInSeat { name: self.player_name(id) };
+        self.announce(id, msg.clone());
+        Ok(self.text_for(id, msg))
+    }
+
+    pub fn table(&self) -> String {
         let last_card = self.discard.last().unwrap();
-        let mut ext = format!("A {} {} has been played!\nIt is currently {}'s turn!\n\n", last_card.id.clone(), last_card.color.clone(), self.queue[0].username); 
-        let mut idx = 1;
-        for player in &self.queue {
-            ext.push_str(format!("{}. {} - {} cards\n",idx,player.username, player.hand.len()).as_str());
-            idx+=1
-        }
-        let mins = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()/60) as i64 - self.time_started;
-        ext.push_str(format!("This game has lasted {} minutes and {} cards have been drawn", mins, self.drawn).as_str());
+        if self.is_over() {
+            return self.text(Msg::LastCardPlayed { card: last_card.text(self.locale), scoreboard: self.scoreboard() })
+        }
+        let mut ext = self.text(Msg::CardOnTable { card: last_card.text(self.locale), name: self.players[&self.queue[0]].username.clone() });
+        ext.push_str("\n\n");
+        for (idx, id) in (1..).zip(self.queue.iter()) {
+            let player = &self.players[id];
+            ext.push_str(self.text(Msg::TableRow { seat: idx, name: player.username.clone(), count: player.hand.len() }).as_str());
+            ext.push('\n');
+        }
+        let mins = self.minutes() - self.time_started;
+        ext.push_str(self.text(Msg::GameLasting { minutes: mins, drawn: self.drawn }).as_str());
         ext
     }
 
-}
\ No newline at end of file
+}
+
+//Spectators
+impl UnoGame {
+    pub fn add_spectator(&mut self, id: impl Into<PlayerId>, name: &str) -> Result<&Spectator, String> {
+        let id = id.into();
+        if self.players.contains_key(&id) {
+            return Err(self.text_for(&id, Msg::AlreadyPlaying { name: self.players[&id].username.clone() }))
+        }
+        if self.spectators.contains_key(&id) {
+            return Err(self.text_for(&id, Msg::AlreadyWatching { name: self.spectators[&id].username.clone() }))
+        }
+        let name = self.validate_name(&id, name)?;
+        self.spectators.insert(id.clone(), Spectator::new(id.clone(), name));
+        Ok(&self.spectators[&id])
+    }
+
+    pub fn remove_spectator(&mut self, id: &PlayerId) -> Result<String, String> {
+        match self.spectators.remove(id) {
+            Some(spectator) => Ok(self.text_for(id, Msg::StoppedWatching { name: spectator.username })),
+            None => Err(self.text_for(id, Msg::SpectatorNotFound { id: id.to_string() })),
+        }
+    }
+
+    pub fn is_spectator(&self, id: &PlayerId) -> bool {
+        self.spectators.contains_key(id)
+    }
+
+    pub fn spectators(&self) -> impl Iterator<Item = &Spectator> {
+        self.spectators.values()
+    }
+
+    fn visible_turn(&self) -> Option<usize> {
+        self.turns.checked_sub(self.rules.int(RuleKey::SpectatorDelay) as usize)
+    }
+
+    pub fn spectator_messages(&mut self, id: &PlayerId) -> Result<Vec<String>, String> {
+        let visible = self.visible_turn();
+        let missing = self.text_for(id, Msg::SpectatorNotFound { id: id.to_string() });
+        let spectator = self.spectators.get_mut(id).ok_or(missing)?;
+        let messages = self.feed.iter().skip(spectator.seen)
+            .take_while(|(turn, _)| Some(*turn) <= visible)
+            .map(|(_, msg)| msg.clone())
+            .collect::<Vec<String>>();
+        spectator.seen += messages.len();
+        Ok(messages)
+    }
+
+    pub fn spectate(&self) -> Result<String, String> {
+        let delay = self.rules.int(RuleKey::SpectatorDelay) as usize;
+        if delay == 0 {
+            return Ok(self.spectator_table(false))
+        }
+        let turn = match self.visible_turn() {
+            Some(turn) => turn,
+            None => return Ok(self.text(Msg::SpectatorsBehind { delay })),
+        };
+        let mut game = UnoGame::replay_to(&self.log, self.log.turn_step(turn))?;
+        game.locale = self.locale;
+        Ok(game.spectator_table(self.rules.bool(RuleKey::SpectatorHands)))
+    }
+
+    fn spectator_table(&self, hands: bool) -> String {
+        if !self.started {
+            let names = self.players().map(|player| player.username.clone()).collect::<Vec<String>>().join(", ");
+            return self.text(Msg::WaitingForStart { names })
+        }
+        let mut out = self.table();
+        if hands && !self.is_over() {
+            out.push('\n');
+            for player in self.players() {
+                let cards = player.hand.iter().map(|card| card.text(self.locale)).collect::<Vec<String>>().join(", ");
+                out.push_str(format!("\n{}: {}", player.username, cards).as_str());
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::bots::{Difficulty, Personality};
+    use crate::clock::ManualClock;
+    use crate::notation;
+    use crate::rating;
+    use super::*;
+
+    fn give(game: &mut UnoGame, cards: &[(&str, &str)]) {
+        let id = game.queue[0].clone();
+        let hand = cards.iter().enumerate().map(|(idx, (id, color))| Card::new(id.to_string(), color, 1000 + idx as i32)).collect();
+        game.players.get_mut(&id).unwrap().hand = hand;
+    }
+
+    fn lobby(seed: u64, ids: &[&str]) -> (UnoGame, PlayerId) {
+        let mut game = UnoGame::with_seed(seed);
+        for id in ids {
+            game.add_player(*id, id.to_uppercase().as_str()).unwrap();
+        }
+        (game, PlayerId::from(ids[0]))
+    }
+
+    #[test]
+    fn joins_reject_duplicates_and_bad_names() {
+        let (mut game, host) = lobby(1, &["alice"]);
+        assert_eq!(game.add_player("alice", "Someone").err(), Some("ALICE has already joined this game".to_string()));
+        assert_eq!(game.add_player("bob", "   ").err(), Some("Player names cannot be empty".to_string()));
+        assert!(game.add_player("bob", "b".repeat(MAX_NAME_LENGTH + 1).as_str()).is_err());
+        assert!(game.add_player("bob", "Bo\nb").is_err());
+        assert_eq!(game.add_player("bob", "  Bob ").unwrap().username(), "Bob");
+        assert!(game.add_player("1", "One").is_ok());
+        assert!(game.add_player(1u64, "Snowflake").is_ok());
+        assert!(game.add_player(1u64, "Again").is_err());
+        assert_eq!(game.players().map(|player| player.id().clone()).collect::<Vec<PlayerId>>(), [PlayerId::from("alice"), PlayerId::from("bob"), PlayerId::from("1"), PlayerId::from(1u64)]);
+        game.start(&host).unwrap();
+        assert!(game.players().all(|player| player.cards().len() == 7));
+    }
+
+    #[test]
+    fn leavers_return_their_cards_and_the_last_player_wins() {
+        for policy in ["deck", "discard"] {
+            let (mut game, host) = lobby(2, &["alice", "bob", "carol"]);
+            game.set_rule(&host, "leaver_cards", policy).unwrap();
+            game.start(&host).unwrap();
+            let (deck, pile, top) = (game.deck_size(), game.discard_pile().len(), game.get_curr_card().clone());
+            game.remove_player(&PlayerId::from("carol")).unwrap();
+            if policy == "deck" {
+                assert_eq!((game.deck_size(), game.discard_pile().len()), (deck + 7, pile));
+            }
+            else {
+                assert_eq!((game.deck_size(), game.discard_pile().len()), (deck, pile + 7));
+            }
+            assert!(game.get_curr_card() == &top);
+            assert!(!game.is_over());
+
+            game.remove_player(&PlayerId::from("bob")).unwrap();
+            assert!(game.is_over());
+            assert!(game.standings().iter().map(|player| player.id()).eq([&PlayerId::from("alice")]));
+            assert_eq!(game.dropped().len(), 2);
+            assert!(game.play("R 1".to_string()).is_err());
+        }
+    }
+
+    #[test]
+    fn late_joiners_get_the_configured_hand_and_a_clamped_seat() {
+        for (option, expected) in [("initial", 7), ("average", 6), ("max", 9)] {
+            let (mut game, host) = lobby(3, &["alice", "bob", "carol"]);
+            game.set_rule(&host, "late_join_hand", option).unwrap();
+            game.start(&host).unwrap();
+            assert!(game.add_player("dave", "Dave").is_err());
+            game.unlock_rules(&host).unwrap();
+            game.set_rule(&host, "allow_late_join", "on").unwrap();
+            game.players.get_mut(&host).unwrap().hand.truncate(3);
+            game.deal(&PlayerId::from("carol"), 2);
+
+            game.add_player_at("dave", "Dave", Some(0)).unwrap();
+            assert_eq!(game.queue[..2], [host.clone(), PlayerId::from("dave")]);
+            assert_eq!(game.players[&PlayerId::from("dave")].hand.len(), expected, "{}", option);
+            game.add_player_at("erin", "Erin", Some(99)).unwrap();
+            assert_eq!(game.queue.last(), Some(&PlayerId::from("erin")));
+        }
+        let (mut game, _) = lobby(3, &["alice", "bob"]);
+        game.add_player_at("carol", "Carol", Some(0)).unwrap();
+        assert_eq!(game.queue[0], PlayerId::from("carol"));
+    }
+
+    #[test]
+    fn players_who_left_cannot_rejoin_the_same_game() {
+        let (mut game, host) = lobby(5, &["ann", "ben", "cat"]);
+        let ben = PlayerId::from("ben");
+        game.set_rule(&host, "allow_late_join", "on").unwrap();
+        game.add_bot(&host, "easy").unwrap();
+        game.start(&host).unwrap();
+        game.remove_player(&ben).unwrap();
+        assert_eq!(game.add_player("ben", "Ben").err(), Some("BEN already played in this game and cannot rejoin it".to_string()));
+        game.remove_player(&PlayerId::from("bot-1")).unwrap();
+        game.add_bot(&host, "easy").unwrap();
+        assert!(game.is_bot(&PlayerId::from("bot-2")));
+
+        game.end(&host).unwrap();
+        let ranked = rating::game_ranks(&game).into_iter().map(|(id, _)| id).collect::<HashSet<PlayerId>>();
+        assert_eq!(ranked.len(), 5);
+    }
+
+    #[test]
+    fn only_the_host_manages_the_game_and_hosting_passes_on() {
+        let (mut game, alice) = lobby(4, &["alice", "bob", "carol"]);
+        let (bob, carol) = (PlayerId::from("bob"), PlayerId::from("carol"));
+        assert!(game.is_host(&alice));
+        assert_eq!(game.start(&bob).err(), Some("Only the host can do that".to_string()));
+        assert!(game.set_rule(&bob, "decks", "2").is_err());
+        assert!(game.kick(&bob, &carol).is_err());
+        assert!(game.kick(&alice, &alice).is_err());
+
+        game.remove_player(&alice).unwrap();
+        assert!(game.is_host(&bob));
+        game.start(&bob).unwrap();
+        assert!(game.set_rule(&bob, "decks", "2").is_err());
+        game.unlock_rules(&bob).unwrap();
+        game.set_rule(&bob, "callout_penalty", "4").unwrap();
+        assert!(game.end(&carol).is_err());
+        game.set_host(&bob, &carol).unwrap();
+        game.end(&carol).unwrap();
+    }
+
+    #[test]
+    fn ending_a_game_ranks_whoever_is_left_by_their_hand() {
+        let (mut game, host) = lobby(6, &["a", "b", "c"]);
+        game.start(&host).unwrap();
+        let ids = ["a", "b", "c"].map(PlayerId::from);
+        let hands = [[("5", "R"), ("9", "G")].as_slice(), &[("9", "Y"), ("5", "B")], &[("SKIP", "R")]];
+        for (id, cards) in ids.iter().zip(hands) {
+            game.players.get_mut(id).unwrap().hand = cards.iter().enumerate().map(|(idx, (card, color))| Card::new(card.to_string(), color, 1000 + idx as i32)).collect();
+        }
+        game.end(&host).unwrap();
+        assert_eq!(game.ranks(), [(ids[2].clone(), 1), (ids[0].clone(), 2), (ids[1].clone(), 2)]);
+        assert!(game.scoreboard().starts_with("1. *C*\n2. *A*\n2. *B*\n"));
+
+        let ratings = rating::rate_game(&game, &HashMap::new());
+        assert_eq!(ratings[&ids[0]], ratings[&ids[1]]);
+        assert!(ratings[&ids[2]].mu > ratings[&ids[0]].mu);
+    }
+
+    #[test]
+    fn votes_pass_once_the_threshold_is_reached() {
+        let (mut game, host) = lobby(5, &["a", "b", "c", "d", "e"]);
+        let ids = ["a", "b", "c", "d", "e"].map(PlayerId::from);
+        assert_eq!(game.vote_kick(&ids[0], &ids[4]).unwrap(), "1/3 votes to kick E");
+        assert_eq!(game.vote_kick(&ids[0], &ids[4]).unwrap(), "1/3 votes to kick E");
+        assert!(game.vote_kick(&ids[4], &ids[4]).is_err());
+        game.vote_kick(&ids[1], &ids[4]).unwrap();
+        assert!(game.vote_kick(&ids[2], &ids[4]).unwrap().starts_with("The vote passed."));
+        assert!(!game.queue.contains(&ids[4]));
+
+        assert!(game.vote_rule(&ids[1], "callout_penalty", "4").is_err());
+        game.set_rule(&host, "vote_rule_threshold", "50").unwrap();
+        assert_eq!(game.vote_rule(&ids[1], "callout_penalty", "4").unwrap(), "1/2 votes to set Callout Penalty to 4");
+        assert!(game.vote_rule(&ids[4], "callout_penalty", "4").is_err());
+        game.vote_rule(&ids[2], "callout_penalty", "4").unwrap();
+        assert_eq!(game.rules.int(RuleKey::CalloutPenalty), 4);
+    }
+
+    #[test]
+    fn play_many_stacks_same_face() {
+        let mut game = UnoGame::with_seed(4);
+        let host = PlayerId::from("alice");
+        for (id, name) in [("alice", "Alice"), ("bob", "Bob"), ("carol", "Carol")] {
+            game.add_player(id, name).unwrap();
+        }
+        game.start(&host).unwrap();
+        game.discard.push(Card::new("7".to_string(), "R", 999));
+        give(&mut game, &[("5", "R"), ("5", "G"), ("5", "B"), ("1", "Y")]);
+        assert!(game.play_many(&["r5", "g5"]).is_err());
+        game.unlock_rules(&host).unwrap();
+        game.set_rule(&host, "multi_play", "on").unwrap();
+        assert!(game.play_many(&["r5", "y1"]).is_err());
+        assert!(game.play_many(&["g5", "r5"]).is_err());
+        game.play_many(&["r5", "g5", "b5"]).unwrap();
+        assert_eq!(game.get_curr_card().to_string(), "Blue 5");
+
+        give(&mut game, &[("+2", "B"), ("+2", "G"), ("3", "Y")]);
+        let target = game.queue[1].clone();
+        let before = game.players[&target].hand.len();
+        game.play_many(&["b+2", "g+2"]).unwrap();
+        assert_eq!(game.players[&target].hand.len(), before + 4);
+        assert_ne!(game.queue[0], target);
+
+        let log = notation::parse(notation::to_notation(game.log()).as_str()).unwrap();
+        assert_eq!(log.entries, game.log().entries);
+        assert!(matches!(&log.entries.last().unwrap().action, Action::PlayMany { card, colors } if card == "+2" && colors == &["B", "G"]));
+    }
+
+    #[test]
+    fn bots_only_call_out_a_missed_uno() {
+        static WATCHFUL: BotPreset = BotPreset {
+            name: "watchful",
+            desc: "",
+            difficulty: Difficulty::Medium,
+            personality: Personality { aggression: 0.5, hoarding: 0.0, callouts: 1.0, forgets_uno: 0.0 },
+        };
+        let mut game = UnoGame::with_seed(5);
+        let host = PlayerId::from("alice");
+        game.add_player("alice", "Alice").unwrap();
+        game.add_bot(&host, "medium").unwrap();
+        game.start(&host).unwrap();
+        let id = PlayerId::from("bot-1");
+        let mut bot = Bot::new(&WATCHFUL, 1);
+        assert!(!bot.wants_callout(&game, &id));
+        game.players.get_mut(&host).unwrap().hand.truncate(1);
+        assert!(bot.wants_callout(&game, &id));
+        game.uno(&host).unwrap();
+        assert!(!bot.wants_callout(&game, &id));
+
+        game.players.get_mut(&host).unwrap().called = false;
+        game.unlock_rules(&host).unwrap();
+        game.set_rule(&host, "callout_penalty", "0").unwrap();
+        assert!(!bot.wants_callout(&game, &id));
+    }
+
+    fn timed_game(action: &str) -> (UnoGame, ManualClock, PlayerId) {
+        let clock = ManualClock::new(1_000_000);
+        let mut game = UnoGame::with_seed(6);
+        game.set_clock(clock.clone());
+        let host = PlayerId::from("alice");
+        for (id, name) in [("alice", "Alice"), ("bob", "Bob"), ("carol", "Carol")] {
+            game.add_player(id, name).unwrap();
+        }
+        for (rule, value) in [("turn_timeout", "30"), ("time_bank", "20"), ("timeout_action", action), ("afk_limit", "2")] {
+            game.set_rule(&host, rule, value).unwrap();
+        }
+        game.start(&host).unwrap();
+        (game, clock, host)
+    }
+
+    #[test]
+    fn timeouts_use_the_bank_then_remove_after_the_afk_limit() {
+        let (mut game, clock, _) = timed_game("remove");
+        let slow = game.queue[0].clone();
+        let hand = game.players[&slow].hand.len();
+        clock.advance(Duration::from_secs(40));
+        assert_eq!(game.time_left(), Some(10_000));
+        assert!(game.tick().is_empty());
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(game.tick().len(), 1);
+        assert_eq!(game.players[&slow].hand.len(), hand + 1);
+        assert_ne!(game.queue[0], slow);
+
+        while game.queue[0] != slow {
+            let id = game.queue[0].clone();
+            game.make_move(&game.legal_moves(&id)[0]).unwrap();
+        }
+        assert_eq!(game.time_left(), Some(30_000));
+        clock.advance(Duration::from_secs(30));
+        game.tick();
+        assert!(!game.queue.contains(&slow));
+        assert_eq!(game.dropped()[0].id(), &slow);
+        let removed = game.log().entries.last().unwrap();
+        assert!(removed.by == slow && removed.action == Action::RemoveAfk);
+
+        let replayed = UnoGame::replay(game.log()).unwrap();
+        assert_eq!(replayed.queue, game.queue);
+        assert_eq!(replayed.banks, game.banks);
+        assert!(game.to_notation().contains(": AFK {"));
+        assert_eq!(UnoGame::from_notation(game.to_notation().as_str()).unwrap().log(), game.log());
+    }
+
+    #[test]
+    fn a_bot_stands_in_until_the_player_returns() {
+        let (mut game, clock, _) = timed_game("bot");
+        let away = game.queue[0].clone();
+        clock.advance(Duration::from_secs(50));
+        assert!(game.tick().len() >= 2);
+        assert!(game.is_bot(&away));
+        assert_ne!(game.queue[0], away);
+        game.return_to_seat(&away).unwrap();
+        assert!(!game.is_bot(&away));
+        assert!(game.return_to_seat(&away).is_err());
+    }
+}